@@ -0,0 +1,76 @@
+use serde::Serialize;
+use std::fmt;
+use std::io;
+use std::path::Path;
+
+// What went wrong in a command, sent to the frontend as { kind, message } so it can pick what to show by the kind
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", content = "message", rename_all = "snake_case")]
+pub enum AppError {
+    NotInCache(String), // The id isn't in the ProjectCache, usually the list is out of date
+    PathMissing(String), // A folder we expected on disk isn't there
+    PermissionDenied(String),
+    InUse(String), // Revit (or something else) has files open
+    Invalid(String), // The request itself can't be done, e.g. an expired token or a version outside the range
+    Io(String),
+}
+
+impl AppError {
+    pub fn not_in_cache(id: &str) -> Self {
+        AppError::NotInCache(format!("Project with ID '{}' not found in cache.", id))
+    }
+
+    // Sorts an io::Error into the closest kind, keeping the path in the message so it's clear what failed
+    pub fn io(path: &Path, e: io::Error) -> Self {
+        // 32 and 33 are Windows' sharing and lock violations, the usual sign Revit still has the model open
+        if matches!(e.raw_os_error(), Some(32) | Some(33)) {
+            return AppError::InUse(format!("{:?} is in use, close Revit first: {}", path, e));
+        }
+
+        match e.kind() {
+            io::ErrorKind::NotFound => AppError::PathMissing(format!("{:?} does not exist.", path)),
+            io::ErrorKind::PermissionDenied => AppError::PermissionDenied(format!("Access to {:?} was denied: {}", path, e)),
+            _ => AppError::Io(format!("{:?}: {}", path, e)),
+        }
+    }
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (AppError::NotInCache(message)
+            | AppError::PathMissing(message)
+            | AppError::PermissionDenied(message)
+            | AppError::InUse(message)
+            | AppError::Invalid(message)
+            | AppError::Io(message)) = self;
+        f.write_str(message)
+    }
+}
+
+// Anything without a path to go on ends up as a plain Io error
+impl From<io::Error> for AppError {
+    fn from(e: io::Error) -> Self {
+        match e.kind() {
+            io::ErrorKind::PermissionDenied => AppError::PermissionDenied(e.to_string()),
+            _ => AppError::Io(e.to_string()),
+        }
+    }
+}
+
+impl From<tauri::Error> for AppError {
+    fn from(e: tauri::Error) -> Self {
+        AppError::Io(e.to_string())
+    }
+}
+
+impl From<tauri_plugin_opener::Error> for AppError {
+    fn from(e: tauri_plugin_opener::Error) -> Self {
+        AppError::Io(e.to_string())
+    }
+}
+
+impl From<serde_json::Error> for AppError {
+    fn from(e: serde_json::Error) -> Self {
+        AppError::Io(e.to_string())
+    }
+}
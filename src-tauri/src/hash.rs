@@ -0,0 +1,73 @@
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::Path;
+use std::time::UNIX_EPOCH;
+use walkdir::WalkDir;
+
+// How much of each end of a file goes into a sampled hash, most model files differ in their header or their tail
+const SAMPLE_SIZE: u64 = 64 * 1024;
+
+// A summary of everything in a folder that only matches another folder's if the contents (very likely) match too
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fingerprint {
+    pub hash: String,
+    pub file_count: u64,
+    pub total_bytes: u64,
+}
+
+// Hashes a folder's files in a fixed order along with where they sit in it. A sampled hash covers each file's size,
+// modified time and the bytes at either end, which is quick enough for big caches. A full one reads every byte and
+// leaves the times out, so only the contents count.
+pub fn fingerprint(folder: &Path, full: bool) -> io::Result<Fingerprint> {
+    let mut hasher = blake3::Hasher::new();
+    let mut file_count = 0;
+    let mut total_bytes = 0;
+
+    for entry in WalkDir::new(folder).sort_by_file_name() {
+        let entry = entry?;
+        if !entry.file_type().is_file() {
+            continue;
+        }
+
+        // The path inside the folder, with the separators evened out so the same layout always hashes the same
+        let relative = entry.path().strip_prefix(folder).unwrap_or(entry.path());
+        hasher.update(relative.to_string_lossy().replace('\\', "/").as_bytes());
+
+        let metadata = entry.metadata()?;
+        let size = metadata.len();
+        hasher.update(&size.to_le_bytes());
+
+        let mut file = File::open(entry.path())?;
+        if full {
+            io::copy(&mut file, &mut hasher)?;
+        } else {
+            let modified = metadata.modified().ok()
+                .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+                .map(|since_epoch| since_epoch.as_secs())
+                .unwrap_or(0);
+            hasher.update(&modified.to_le_bytes());
+            hash_samples(&mut file, size, &mut hasher)?;
+        }
+
+        file_count += 1;
+        total_bytes += size;
+    }
+
+    Ok(Fingerprint { hash: hasher.finalize().to_hex().to_string(), file_count, total_bytes })
+}
+
+// Feeds the start of a file and, if it's big enough to have one that doesn't overlap, the end
+fn hash_samples(file: &mut File, size: u64, hasher: &mut blake3::Hasher) -> io::Result<()> {
+    let mut buffer = Vec::with_capacity(SAMPLE_SIZE as usize);
+    file.by_ref().take(SAMPLE_SIZE).read_to_end(&mut buffer)?;
+    hasher.update(&buffer);
+
+    if size > SAMPLE_SIZE * 2 {
+        buffer.clear();
+        file.seek(SeekFrom::End(-(SAMPLE_SIZE as i64)))?;
+        file.by_ref().take(SAMPLE_SIZE).read_to_end(&mut buffer)?;
+        hasher.update(&buffer);
+    }
+
+    Ok(())
+}
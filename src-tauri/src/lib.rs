@@ -1,250 +1,4278 @@
-use tauri::Manager;
+mod error;
+mod hash;
+mod logging;
+mod metadata;
+mod settings;
+mod watch;
+
+use tauri::{Emitter, Manager};
 use tauri::{AppHandle, State};
 use tauri::async_runtime::Mutex;
 use tauri_plugin_opener::OpenerExt;
+use tauri_plugin_clipboard_manager::ClipboardExt;
+use base64::Engine;
 use std::fs;
-use std::path::PathBuf;
-use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
 use walkdir::WalkDir;
-use serde::Serialize;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use tracing::{debug, error, info, warn};
+use error::AppError;
+use metadata::NameCache;
+use settings::{Settings, SettingsState};
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+struct ProjectDef {
+      id: String,
+    name: String,
+    year: u16,
+    days: u64,
+    oldest_days: u64,
+    last_opened: Option<u64>, // Unix seconds, from Revit's session log if there is one, otherwise the newest file
+    size: u64,
+    size_display: String, // The size already formatted for showing, size stays around for sorting
+    on_disk_size: u64,
+    file_count: u64,
+    revision_count: u64, // Revision folders directly under the project, Revit keeps one per cached revision (see is_revision_folder)
+    user_folder: String, // The user id folder the project sits in, one per Autodesk account that has used the machine
+    central_guid: String,
+    server_hint: Option<String>,
+    root: String,
+    #[serde(default)]
+    path_display: String, // The folder's full path for showing, never used to find it again, that's what the id is for
+    orphaned: bool,
+    pinned: bool, // Pinned projects can't be deleted until they're unpinned
+    #[serde(default)]
+    in_use: bool, // The model looked to be open in Revit when the scan ran, a delete would fail
+    #[serde(default)]
+    score: f64, // How worth deleting the project is, bigger and older scores higher, see staleness_score
+    #[serde(default)]
+    depth_capped: bool, // The walk stopped at the depth limit, the size and file count may be too low
+    #[serde(default)]
+    empty: bool, // No files or only empty ones, usually left behind by a crash, so there's nothing to lose deleting it
+}
+
+// Sent while a permanent delete works through a project's files, so a big folder doesn't look like a hang
+#[derive(Debug, Serialize, Clone)]
+struct DeleteProgress {
+    id: String,
+    files_removed: u64,
+    bytes_freed: u64,
+}
+
+// Sent as each project in a batch delete finishes, whether or not it worked
+#[derive(Debug, Serialize, Clone)]
+struct DeleteBatchProgress {
+    id: String,
+    completed: usize,
+    total: usize,
+}
+
+// Why clean_all left a project where it was
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "snake_case")]
+enum SkipReason {
+    NotConfirmed, // Wasn't part of the prepared delete, likely found by a scan after the confirmation was shown
+    Pinned,
+    InUse,
+    Failed(AppError),
+}
+
+#[derive(Debug, Serialize, Clone)]
+struct SkippedProject {
+    id: String,
+    name: String,
+    reason: SkipReason,
+}
+
+#[derive(Debug, Serialize, Clone)]
+struct CleanAllResult {
+    bytes_freed: u64,
+    deleted_count: usize,
+    skipped: Vec<SkippedProject>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+struct ScanProgress {
+    year: u16,
+    discovered: usize,
+    percent: u8,
+}
+
+#[derive(Debug, Serialize, Clone)]
+struct ScanComplete {
+    total: usize,
+    timings: ScanTimings,
+}
+
+// How long a scan spent where, to go on when one is slow on somebody's machine
+#[derive(Debug, Serialize, Clone, Default)]
+struct ScanTimings {
+    total_ms: u64,
+    find_ms: u64, // Listing the version, user and project folders
+    walk_ms: u64, // Walking every project folder, in parallel
+    dirs_visited: u64,
+    files_statted: u64,
+    slowest_project: Option<SlowestWalk>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+struct SlowestWalk {
+    id: String,
+    name: String,
+    walk_ms: u64,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "lowercase")]
+enum DeleteOutcome {
+    Success,
+    #[serde(rename = "dry_run")]
+    DryRun { bytes_freed: u64 }, // Dry run mode left the folder alone, this is what deleting it would have freed
+    Error(AppError),
+}
+
+#[derive(Debug, Serialize, Clone)]
+struct DeletePreview {
+    total_bytes: u64,
+    total_files: u64,
+    folders: HashMap<String, u64>,
+    missing: Vec<String>,
+}
+
+// What trim_project got rid of
+#[derive(Debug, Serialize, Clone)]
+struct TrimResult {
+    bytes_freed: u64,
+    files_removed: u64,
+    failed: Vec<ScanWarning>, // Old files that couldn't be removed, the rest of the trim still went ahead
+}
+
+// A rough idea of how big a delete is and how long it might take, so a long one doesn't look like a hang
+#[derive(Debug, Serialize, Clone)]
+struct DeleteEstimate {
+    total_bytes: u64,
+    total_files: u64,
+    estimated_secs: u64, // Only a ballpark, disks and the recycle bin vary a lot
+}
+
+// The orderings get_projects can return its list in, sent from the frontend as strings like "size_desc"
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default)]
+#[serde(rename_all = "snake_case")]
+enum ProjectSort {
+    #[default]
+    ScoreDesc, // Highest `score` first, the projects most worth deleting
+    SizeDesc, // Largest `size` first
+    AgeDesc,  // Largest `days` first, i.e. the stalest projects
+    NameAsc,  // `name` alphabetically
+    YearAsc,  // `year` from oldest Revit version to newest
+}
+
+#[derive(Debug, Serialize, Clone, Default)]
+struct CacheTotals {
+    total_bytes: u64,
+    project_count: usize,
+}
+
+// One range of ages in get_age_histogram, max_days is None for the last one which has no upper end
+#[derive(Debug, Serialize, Clone)]
+struct AgeBucket {
+    min_days: u64,
+    max_days: Option<u64>,
+    totals: CacheTotals,
+}
+
+#[derive(Debug, Serialize, Clone)]
+struct OpenProjectsResult {
+    opened: Vec<String>, // Ids that got a window
+    errors: HashMap<String, AppError>, // Ids that couldn't be opened and why
+    warning: Option<String>, // Set if there were more ids than MAX_OPEN_AT_ONCE and the rest were left
+}
+
+#[derive(Debug, Serialize, Clone)]
+struct PermissionCheck {
+    path: String, // The folder that was checked
+    can_delete: bool,
+    error: Option<AppError>, // What went wrong if we can't, a permission_denied usually means it needs admin rights
+}
+
+#[derive(Debug, Serialize, Clone)]
+struct SelectedExport {
+    path: String,
+    warnings: Vec<String>, // One for each id that couldn't be found
+}
+
+#[derive(Debug, Serialize, Clone)]
+struct BaseReset {
+    path: Option<String>, // None if there's no local app data folder to put the default in
+    warning: Option<String>,
+}
+
+// Everywhere a scan looks, for showing in the UI
+#[derive(Debug, Serialize, Clone)]
+struct ScanLocations {
+    base: Option<String>,
+    custom_base: bool, // The base was picked by the user rather than being the default
+    extra_roots: Vec<String>,
+}
+
+// How big a scan would be, so the frontend can warn before starting a slow one
+#[derive(Debug, Serialize, Clone, Default)]
+struct ScanCost {
+    project_count: usize,
+    on_network: bool, // The base or an extra root is on a network share, where every file is a round trip
+}
+
+// What clearing the whole cache would free, and how much of it would be left alone
+#[derive(Debug, Serialize, Clone)]
+struct CleanAllPreview {
+    reclaimable: CacheTotals, // Everything that would actually be deleted
+    protected: CacheTotals, // Pinned projects and ones Revit has open, which a clean all skips
+}
+
+#[derive(Debug, Serialize, Clone)]
+struct DiskSpace {
+    total_bytes: u64,
+    available_bytes: u64,
+}
+
+// Whether two projects' caches hold the same files, from compare_projects
+#[derive(Debug, Serialize, Clone)]
+struct ContentComparison {
+    identical: bool, // Certain for a full comparison, very likely for a sampled one
+    full: bool,
+    first_hash: String,
+    second_hash: String,
+    first_file_count: u64,
+    second_file_count: u64,
+}
+
+// The size of any folder inside the cache, from measure_path
+#[derive(Debug, Serialize, Clone)]
+struct PathMeasure {
+    total_bytes: u64,
+    file_count: u64,
+}
+
+// A folder that had to be skipped during a scan, and why
+#[derive(Debug, Serialize, Clone)]
+struct ScanWarning {
+    path: String,
+    message: String,
+}
+
+#[derive(Debug, Serialize, Clone)]
+struct ScanResult {
+    projects: Vec<ProjectDef>,
+    warnings: Vec<ScanWarning>,
+    truncated: bool, // The scan was cancelled or ran out of time, so some projects are missing
+    discovery: CacheDiscovery,
+    timings: Option<ScanTimings>, // None if the results came from the last scan rather than a fresh one
+    most_recent: Option<RecentProject>, // Also None from the last scan, the exact file times aren't kept
+}
+
+#[derive(Debug, Serialize, Clone)]
+struct RecentProject {
+    id: String,
+    name: String,
+    modified_at: u64, // Unix seconds of the newest file in it
+}
+
+// Which cache folders were actually there, so an empty list can be told apart from looking in the wrong place
+#[derive(Debug, Serialize, Clone, PartialEq)]
+struct CacheDiscovery {
+    found_versions: Vec<u16>, // Versions in the range with a CollaborationCache folder
+    missing_versions: Vec<u16>, // Versions in the range without one, usually just not installed
+    missing_roots: Vec<String>, // Extra roots that weren't there, a drive that isn't plugged in or a folder that moved
+    cache_found: bool, // At least one cache folder was there to look in, false most likely means Revit isn't installed
+}
+
+// The last scan as get_last_results hands it back, without walking anything
+#[derive(Debug, Serialize, Clone)]
+struct LastResults {
+    projects: Vec<ProjectDef>,
+    scanned_at: Option<u64>,
+    stale: bool,
+}
+
+// The projects for a single Revit version (or user folder) along with their totals
+#[derive(Debug, Serialize, Clone, Default)]
+struct ProjectGroup {
+    projects: Vec<ProjectDef>,
+    total_size: u64,
+    total_count: usize,
+}
+
+impl ProjectGroup {
+    fn push(&mut self, project: ProjectDef) {
+        self.total_size += project.size;
+        self.total_count += 1;
+        self.projects.push(project);
+    }
+}
+
+// Caches of the same central model found under more than one version, newest version first
+#[derive(Debug, Serialize, Clone)]
+struct DuplicateGroup {
+    central_guid: String,
+    projects: Vec<ProjectDef>,
+    total_size: u64,
+}
+
+#[derive(Debug, Serialize, Clone)]
+struct GroupedScanResult {
+    versions: BTreeMap<u16, ProjectGroup>,
+    warnings: Vec<ScanWarning>,
+    truncated: bool,
+    discovery: CacheDiscovery,
+}
+
+#[derive(Debug, Serialize, Clone)]
+struct UserGroupedScanResult {
+    users: BTreeMap<String, ProjectGroup>,
+    warnings: Vec<ScanWarning>,
+    truncated: bool,
+    discovery: CacheDiscovery,
+}
+
+// Handed back by prepare_delete, the token has to be passed to the delete commands before it expires
+#[derive(Debug, Serialize, Clone)]
+struct DeletePreparation {
+    token: String,
+    expires_in_secs: u64,
+    preview: DeletePreview,
+}
+
+// A delete that has been previewed and is waiting to be confirmed
+struct PendingDeleteToken {
+    token: String,
+    ids: Vec<String>,
+    created: Instant,
+}
+
+// A Revit version that has a CollaborationCache folder, and how much is in it
+#[derive(Debug, Serialize, Clone)]
+struct InstalledVersion {
+    year: u16,
+    project_count: usize, // Project folders across every user folder, counted without walking into any of them
+    size: Option<u64>, // Only filled in when asked for, sizing means walking the whole version folder
+}
+
+// Everything written out by export_json, bump EXPORT_SCHEMA_VERSION whenever the shape of this changes
+#[derive(Debug, Serialize)]
+struct JsonExport {
+    schema_version: u32,
+    exported_at: u64, // Seconds since the Unix epoch
+    totals: CacheTotals,
+    projects: Vec<ProjectDef>,
+}
+
+// Everything run_report hands back, for scripts that want the whole picture from one call. Bump REPORT_SCHEMA_VERSION
+// whenever the shape of this changes.
+#[derive(Debug, Serialize)]
+struct CacheReport {
+    schema_version: u32,
+    generated_at: u64, // Seconds since the Unix epoch
+    host_name: Option<String>,
+    totals: CacheTotals,
+    versions: BTreeMap<u16, CacheTotals>,
+    projects: Vec<ProjectDef>,
+    warnings: Vec<ScanWarning>,
+    truncated: bool,
+}
+
+// How much of a project is taken up by one file extension
+#[derive(Debug, Serialize, Clone)]
+struct ExtensionBreakdown {
+    extension: String, // Lowercased, "(none)" for files without one
+    count: u64,
+    total_bytes: u64,
+}
+
+// Sent by the watcher when a project's folder changes, so the frontend can update that one row
+#[derive(Debug, Serialize, Clone)]
+struct ProjectChanged {
+    id: String,
+    size: u64,
+    size_display: String,
+    removed: bool, // The folder is gone, the row should go too
+}
+
+pub struct ProjectCache(pub Mutex<HashMap<String, PathBuf>>);
+
+// The projects returned by the last scan, kept so they can be saved on exit and handed back on the next launch
+struct LastScan {
+    projects: Mutex<Vec<ProjectDef>>,
+    stamp: Mutex<ScanStamp>,
+}
+
+impl LastScan {
+    // Something has changed on disk since the scan, the list is still worth showing but shouldn't be trusted
+    async fn mark_stale(&self) {
+        self.stamp.lock().await.stale = true;
+    }
+}
+
+// When the last scan was taken, and whether anything has happened since that makes it out of date
+#[derive(Debug, Serialize, Clone, Copy, Default)]
+struct ScanStamp {
+    scanned_at: Option<u64>, // Unix seconds
+    stale: bool,
+}
+
+// What gets written to disk on exit so the next launch can show the list without walking everything
+#[derive(Debug, Serialize, Deserialize, Default)]
+struct PersistedProjects {
+    projects: Vec<(ProjectDef, PathBuf)>,
+    #[serde(default)]
+    scanned_at: Option<u64>,
+}
+
+// Only the most recently prepared delete can be confirmed, preparing again replaces it
+pub struct PendingDelete(Mutex<Option<PendingDeleteToken>>);
+
+pub struct CancellationToken(pub Arc<AtomicBool>);
+
+// Set while a scan is running, the scan clears and refills the ProjectCache so two at once would mix their entries up
+pub struct ScanInProgress(AtomicBool);
+
+impl ScanInProgress {
+    // Claims the scan, or errors if one is already running
+    fn start(&self) -> Result<ScanGuard<'_>, AppError> {
+        self.0.compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
+            .map(|_| ScanGuard(&self.0))
+            .map_err(|_| AppError::InUse("A scan is already in progress.".to_string()))
+    }
+}
+
+// Lets go of the scan when it's dropped, so returning early can't leave it stuck
+struct ScanGuard<'a>(&'a AtomicBool);
+
+impl Drop for ScanGuard<'_> {
+    fn drop(&mut self) {
+        self.0.store(false, Ordering::Release);
+    }
+}
+
+// The background task started by start_auto_refresh, if it's running
+pub struct AutoRefresh(Mutex<Option<tauri::async_runtime::JoinHandle<()>>>);
+
+// The filesystem watcher started by start_watch, dropping it stops the watch
+pub struct FolderWatch(Mutex<Option<notify::RecommendedWatcher>>);
+
+// Projects deleted by moving them aside rather than removing them, so they can be put back until the quarantine is purged
+pub struct Quarantine {
+    dir: Option<PathBuf>, // This session's folder under QUARANTINE_DIR, None if the local app data directory couldn't be found
+    entries: Mutex<Vec<QuarantinedProject>>, // Oldest first, so the last one is the most recent delete
+}
+
+struct QuarantinedProject {
+    id: String,
+    original: PathBuf,
+    quarantined: PathBuf,
+    project: Option<ProjectDef>, // The row from the last scan, so a restore can put it straight back in the list
+}
+
+impl Quarantine {
+    // Where everything in the quarantine was moved from
+    async fn original_paths(&self) -> HashSet<PathBuf> {
+        self.entries.lock().await.iter().map(|entry| entry.original.clone()).collect()
+    }
+}
+
+// The Revit versions to look for under the base path, defaults to MINIMUM_VERSION and up
+#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+struct VersionRange {
+    min: u16,
+    #[serde(default)]
+    max: Option<u16>, // None for no upper limit, so a new Revit release gets picked up without touching the settings
+}
+
+impl Default for VersionRange {
+    fn default() -> Self {
+        VersionRange { min: MINIMUM_VERSION, max: None }
+    }
+}
+
+impl VersionRange {
+    fn contains(&self, vers: u16) -> bool {
+        vers >= self.min && self.max.is_none_or(|max| vers <= max)
+    }
+}
+
+// A copy of the current paths taken at the start of a command, so nothing stays locked during a long walk
+struct ScanRoots {
+    revit_cc_base: Option<PathBuf>,
+    custom_base: bool, // The base path was picked by the user rather than being the default location
+    extra_roots: Vec<PathBuf>,
+    version_range: VersionRange,
+}
+
+impl ScanRoots {
+    // The Revit years in the version range that have a folder under the base path, oldest first. Reading the base once
+    // is quicker than checking every year in the range, and a new release gets picked up as soon as it's installed.
+    fn installed_years(&self) -> Vec<u16> {
+        let Some(entries) = self.revit_cc_base.as_ref().and_then(|revit_cc_base| fs::read_dir(revit_cc_base).ok()) else {
+            return Vec::new();
+        };
+
+        let mut years: Vec<u16> = entries.flatten()
+            .filter(|entry| entry.file_type().is_ok_and(|file_type| file_type.is_dir()))
+            .filter_map(|entry| parse_version_folder(&entry.file_name().to_string_lossy()))
+            .filter(|vers| self.version_range.contains(*vers))
+            .collect();
+        years.sort_unstable();
+        years
+    }
+
+    // Every CollaborationCache folder that exists right now, one per installed version plus the extra roots
+    fn cache_dirs(&self) -> Vec<PathBuf> {
+        let version_dirs = self.revit_cc_base.iter().flat_map(|revit_cc_base| {
+            self.installed_years().into_iter().map(move |vers| version_cache_path(revit_cc_base, vers))
+        });
+        version_dirs
+            .chain(self.extra_roots.iter().cloned())
+            .filter(|dir| dir.is_dir())
+            .collect()
+    }
+
+    // The base path, or an error the frontend can show if we never managed to find one or it can't be scanned. The
+    // default location not being there just means Revit hasn't made a cache yet, which isn't an error.
+    fn base(&self) -> Result<&Path, AppError> {
+        let base = self.revit_cc_base.as_deref().ok_or_else(|| AppError::PathMissing(BASE_PATH_UNAVAILABLE.to_string()))?;
+        if base.is_file() {
+            return Err(AppError::Invalid(format!("The Revit cache path {:?} is a file, please choose its folder instead.", base)));
+        }
+        if self.custom_base && !base.is_dir() {
+            return Err(AppError::PathMissing(format!("The Revit cache path {:?} does not exist, please choose it again.", base)));
+        }
+        Ok(base)
+    }
+}
+
+const MINIMUM_VERSION: u16 = 2018;
+
+// Shown when there's no base path to work with, picking one with set_base_path fixes it
+const BASE_PATH_UNAVAILABLE: &str = "Could not locate the Revit cache directory, please choose it manually.";
+
+// File in the app data directory the project cache is saved to on exit
+const PROJECTS_FILE: &str = "project_cache.json";
+
+// Tuning for staleness_score. Sizes are counted in GiB, the offset keeps the log finite for a project touched today (it
+// scores zero rather than negative infinity), and the factor scales the age weighting against the size.
+const SCORE_SIZE_UNIT: f64 = 1024.0 * 1024.0 * 1024.0;
+const SCORE_AGE_OFFSET_DAYS: f64 = 1.0;
+const SCORE_AGE_FACTOR: f64 = 1.0;
+
+// Folder in the local app data directory that quarantined deletes are moved into, one subfolder per session. It has to
+// be the local one, the roaming one can be redirected to a server and gigabytes of cache would get copied there.
+const QUARANTINE_DIR: &str = "quarantine";
+
+// Points the default base path somewhere else, for trying the app against a made up CollaborationCache tree without
+// Revit installed. A base path picked in the app still wins over it.
+const CACHE_BASE_ENV: &str = "CCC_CACHE_BASE";
+
+// Where get_age_histogram splits projects up if the frontend doesn't say, in days since they were last touched
+const DEFAULT_AGE_BUCKET_ENDS: [u64; 3] = [7, 30, 90];
+
+// Most Explorer windows open_projects will open in one go, any more than this and the screen fills up
+const MAX_OPEN_AT_ONCE: usize = 10;
+
+// Start of the throwaway file check_permissions writes, the rest is a fresh UUID so it can't clash with anything
+const PERMISSION_MARKER_PREFIX: &str = ".ccc-permission-check-";
+
+// Version of the export_json format, so anything reading the files can tell when it changes
+const EXPORT_SCHEMA_VERSION: u32 = 1;
+
+// Version of the run_report format, the same idea as EXPORT_SCHEMA_VERSION
+const REPORT_SCHEMA_VERSION: u32 = 1;
+
+// How long a prepared delete stays valid for
+const DELETE_TOKEN_LIFETIME: Duration = Duration::from_secs(60);
+
+// Projects with nothing modified in this many days are flagged as orphaned, kept high so active projects aren't touched
+const DEFAULT_ORPHAN_DAYS: u64 = 180;
+
+// Revit's own layout is only a few folders deep, so this only ever cuts off something odd like a junction loop on a
+// redirected profile
+const DEFAULT_MAX_WALK_DEPTH: usize = 12;
+
+// Anything shallower wouldn't reach the files inside each revision folder, and every project would look near empty
+const MIN_WALK_DEPTH: usize = 4;
+
+// How many files a permanent delete gets through between each delete-progress event
+const DELETE_PROGRESS_EVERY: u64 = 200;
+
+// How many folders a batch delete works on at once when the cache is on an SSD, and the most anyone can ask for
+const SSD_DELETE_CONCURRENCY: usize = 4;
+const MAX_DELETE_CONCURRENCY: usize = 16;
+
+// How long to wait before each retry of a failed delete, getting longer each time
+const DELETE_RETRY_DELAYS: [Duration; 3] = [
+    Duration::from_millis(100),
+    Duration::from_millis(300),
+    Duration::from_millis(700),
+];
+
+// Auto refresh can't run more often than this, a check walks every project so it isn't free
+const MIN_AUTO_REFRESH_SECS: u64 = 30;
+
+// The total size has to move by at least this much before auto refresh reports it, small caches churn constantly
+const AUTO_REFRESH_MIN_CHANGE: u64 = 10 * 1024 * 1024;
+
+// Rough delete speeds for estimate_delete, on the slow side so the estimate doesn't undersell it
+const DELETE_FILES_PER_SEC: u64 = 1000;
+const DELETE_BYTES_PER_SEC: u64 = 200 * 1024 * 1024;
+
+// How many walk entries to process between checks of the cancellation flag
+const CANCEL_CHECK_INTERVAL: usize = 256;
+
+impl ScanWarning {
+    fn new(path: &Path, error: impl std::fmt::Display) -> Self {
+        ScanWarning {
+            // Drop any \\?\ prefix from the walk so the path reads normally in the UI
+            path: dunce::simplified(path).to_string_lossy().into_owned(),
+            message: error.to_string(),
+        }
+    }
+}
+
+// How a scan should be run and what it should return, passed in from the frontend or taken from the saved settings if it
+// doesn't send any. Anything left out falls back to the defaults below.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+struct ScanOptions {
+    sort: ProjectSort, // Defaults to score descending so the projects most worth deleting surface first
+    min_size_bytes: Option<u64>, // Projects smaller than this are left out
+    min_days: Option<u64>, // Projects younger than this are left out
+    orphan_days: u64, // Projects untouched for this many days are flagged as orphaned
+    excluded_folders: Vec<String>, // Subfolder names (e.g. "Logs") left out of the size and age, in any case
+    max_duration_secs: Option<u64>, // Stop walking after this long and return whatever was found, no limit by default
+    // Walk into symlinked/junctioned folders. Off by default, a link to a folder that's also scanned gets counted twice
+    // and a link can point right out of the cache.
+    follow_links: bool,
+    max_depth: usize, // How many folders deep to walk under each project, anything further down isn't counted
+}
+
+impl Default for ScanOptions {
+    fn default() -> Self {
+        ScanOptions {
+            sort: ProjectSort::default(),
+            min_size_bytes: None,
+            min_days: None,
+            orphan_days: DEFAULT_ORPHAN_DAYS,
+            excluded_folders: Vec::new(),
+            max_duration_secs: None,
+            follow_links: false,
+            max_depth: DEFAULT_MAX_WALK_DEPTH,
+        }
+    }
+}
+
+impl ScanOptions {
+    // Options from the frontend don't go through the settings, so they get checked here before a scan uses them
+    fn validate(&self) -> Result<(), AppError> {
+        if self.max_depth < MIN_WALK_DEPTH {
+            return Err(AppError::Invalid(format!("The walk depth has to be at least {}.", MIN_WALK_DEPTH)));
+        }
+        Ok(())
+    }
+}
+
+// What can stop a walk partway through, either the user cancelling or the scan running out of time
+struct ScanStop<'a> {
+    cancelled: &'a AtomicBool,
+    deadline: Option<Instant>,
+}
+
+// Backs ScanStop::never, nothing ever sets it
+static NEVER_CANCELLED: AtomicBool = AtomicBool::new(false);
+
+impl ScanStop<'_> {
+    // For walks outside a scan, which always run to the end
+    fn never() -> ScanStop<'static> {
+        ScanStop { cancelled: &NEVER_CANCELLED, deadline: None }
+    }
+
+    fn should_stop(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed) || self.deadline.is_some_and(|deadline| Instant::now() >= deadline)
+    }
+}
+
+// A project folder found under a user folder, waiting to be walked
+struct ProjectFolder {
+    year: u16,
+    path: PathBuf,
+    name: std::ffi::OsString, // Kept as the raw name so nothing is lost before it's shown
+    root: PathBuf,
+}
+
+// Turns a path into the \\?\ extended-length form so Windows doesn't stop at 260 characters (MAX_PATH).
+// The cache keeps the plain path, this is only for handing to the filesystem.
+#[cfg(windows)]
+fn long_path(path: &Path) -> PathBuf {
+    use std::ffi::OsString;
+    use std::path::{Component, Prefix};
+
+    let mut components = path.components();
+    match components.next() {
+        // Plain drive paths like C:\... just get \\?\ stuck on the front
+        Some(Component::Prefix(prefix)) if matches!(prefix.kind(), Prefix::Disk(_)) && path.is_absolute() => {
+            let mut extended = OsString::from(r"\\?\");
+            extended.push(path.as_os_str());
+            PathBuf::from(extended)
+        }
+        // Network shares use the \\?\UNC\server\share form instead of \\server\share
+        Some(Component::Prefix(prefix)) => match prefix.kind() {
+            Prefix::UNC(server, share) => {
+                let mut extended = OsString::from(r"\\?\UNC\");
+                extended.push(server);
+                extended.push(r"\");
+                extended.push(share);
+
+                // Add back everything after the share, skipping the root separator
+                let mut extended = PathBuf::from(extended);
+                extended.extend(components.filter(|component| !matches!(component, Component::RootDir)));
+                extended
+            }
+            // Already extended-length (or a device path), so leave it alone
+            _ => path.to_path_buf(),
+        },
+        // Relative paths can't be made extended-length
+        _ => path.to_path_buf(),
+    }
+}
+
+// Everywhere else paths aren't limited to MAX_PATH, so they're passed through as-is
+#[cfg(not(windows))]
+fn long_path(path: &Path) -> PathBuf {
+    path.to_path_buf()
+}
+
+// Makes an opaque id for a project folder. It's a UUID hashed from the exact bytes of the path, so it stays the same
+// between scans but can't be mangled by lossy string conversion the way the path itself could.
+fn project_id(path: &Path) -> String {
+    Uuid::new_v5(&Uuid::NAMESPACE_URL, path.as_os_str().as_encoded_bytes()).to_string()
+}
+
+// Converts a file time into how many whole days ago it was, 0 if there isn't one or it's in the future
+fn days_since(time: Option<std::time::SystemTime>) -> u64 {
+    time.and_then(|time| std::time::SystemTime::now().duration_since(time).ok())
+        .map(|duration| duration.as_secs() / 86400) // 86400 = 60*60*24
+        .unwrap_or(0)
+}
+
+// Converts a file time into seconds since the Unix epoch, 0 if it's somehow before it
+fn unix_seconds(time: std::time::SystemTime) -> u64 {
+    time.duration_since(std::time::UNIX_EPOCH)
+        .map(|since_epoch| since_epoch.as_secs())
+        .unwrap_or(0)
+}
+
+// Builds the CollaborationCache path for a given Revit version under the base path
+fn version_cache_path(revit_cc_base: &Path, vers: u16) -> PathBuf {
+    revit_cc_base
+        .join(format!("Autodesk Revit {}", vers))
+        .join("CollaborationCache")
+}
+
+// Parses the year out of a folder named like "Autodesk Revit 2025"
+fn parse_version_folder(name: &str) -> Option<u16> {
+    name.strip_prefix("Autodesk Revit ")?.trim().parse().ok()
+}
+
+// Goes through each user folder in a CollaborationCache folder and adds every project folder found, noting any folder it can't read
+fn find_root_project_folders(cache_root: &Path, year: u16, warnings: &mut Vec<ScanWarning>, project_folders_found: &mut Vec<ProjectFolder>) {
+    // Reading a file as a folder gives a confusing OS error, so say what's actually wrong
+    if cache_root.is_file() {
+        warnings.push(ScanWarning::new(cache_root, "Expected a cache folder but found a file"));
+        return;
+    }
+
+    // Get an iterator of user folders in the cache folder
+    let user_folders = match fs::read_dir(cache_root) {
+        Ok(folders) => folders,
+        Err(e) => {
+            warnings.push(ScanWarning::new(cache_root, e));
+            return;
+        }
+    };
+
+    // Loop over the user folders
+    for user_folder in user_folders {
+
+        // Check that we've gotten a user folder on this iteration
+        let user_folder_entry = match user_folder {
+            Ok(entry) => entry,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(e) => {
+                warnings.push(ScanWarning::new(cache_root, e));
+                continue;
+            }
+        };
+
+        // Check that it's a directory
+        let user_folder_path = user_folder_entry.path();
+        if !user_folder_path.is_dir() {
+            continue;
+        }
+
+        // Get an iterator for the project folders in the current user folder
+        let project_folders = match fs::read_dir(&user_folder_path) {
+            Ok(folders) => folders,
+            // Revit cleared the folder out between us listing it and opening it, nothing to warn about
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(e) => {
+                warnings.push(ScanWarning::new(&user_folder_path, e));
+                continue;
+            }
+        };
+        let found_before = project_folders_found.len();
+
+        // Loop over the project folders
+        for project_folder in project_folders {
+
+            // Check that we've gotten a project folder this iteration
+            let project_folder_entry = match project_folder {
+                Ok(entry) => entry,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(e) => {
+                    warnings.push(ScanWarning::new(&user_folder_path, e));
+                    continue;
+                }
+            };
+
+            // Get the name of the folder itself to show in the UI
+            let project_name = project_folder_entry.file_name();
+
+            // Queue the project up to be walked
+            project_folders_found.push(ProjectFolder {
+                year,
+                path: project_folder_entry.path(),
+                name: project_name,
+                root: cache_root.to_path_buf(),
+            });
+        }
+
+        debug!(user_folder = ?user_folder_path, projects = project_folders_found.len() - found_before, "Read user folder");
+    }
+}
+
+// Lists every project folder under each version of the base path and under any extra roots
+fn find_project_folders(roots: &ScanRoots, warnings: &mut Vec<ScanWarning>) -> Vec<ProjectFolder> {
+    // Container vector for the project folders waiting to be walked
+    let mut project_folders_found = Vec::new();
+
+    // Iterate over each installed year, if there's no base path there are no versions to look through
+    // Each path is: %localappdata%/Autodesk/Revit/Autodesk Revit {vers}/CollaborationCache
+    let version_paths = roots.revit_cc_base.iter().flat_map(|revit_cc_base| {
+        roots.installed_years().into_iter().map(move |vers| (vers, version_cache_path(revit_cc_base, vers)))
+    });
+    for (vers, vers_path) in version_paths {
+
+        // Check that the path exists first, a version that's never opened a central model won't have one
+        if !vers_path.exists() {
+            continue;
+        }
+        if !vers_path.is_dir() {
+            warnings.push(ScanWarning::new(&vers_path, "Expected a CollaborationCache folder but found a file"));
+            continue;
+        }
+
+        let found_before = project_folders_found.len();
+        find_root_project_folders(&vers_path, vers, warnings, &mut project_folders_found);
+        info!(version = vers, projects = project_folders_found.len() - found_before, "Found Revit version cache");
+    }
+
+    // Extra roots don't sit under a version folder of ours, so take the year from the path if it has one (0 if not)
+    for extra_root in &roots.extra_roots {
+        let year = extra_root.ancestors()
+            .filter_map(|ancestor| ancestor.file_name())
+            .find_map(|name| parse_version_folder(&name.to_string_lossy()))
+            .unwrap_or(0);
+
+        let found_before = project_folders_found.len();
+        find_root_project_folders(extra_root, year, warnings, &mut project_folders_found);
+        info!(root = ?extra_root, projects = project_folders_found.len() - found_before, "Read extra cache root");
+    }
+
+    project_folders_found
+}
+
+// Checks which of the folders a scan would look in are there, without walking any of them
+fn discover_cache(roots: &ScanRoots) -> CacheDiscovery {
+    let found_versions: Vec<u16> = match &roots.revit_cc_base {
+        Some(revit_cc_base) => roots.installed_years().into_iter()
+            .filter(|vers| version_cache_path(revit_cc_base, *vers).is_dir())
+            .collect(),
+        None => Vec::new(),
+    };
+    // Without an upper limit there's no end to what could be missing, so only count the gaps up to the newest one found
+    let newest = roots.version_range.max.or(found_versions.last().copied());
+    let missing_versions = newest
+        .map(|newest| (roots.version_range.min..=newest).filter(|vers| !found_versions.contains(vers)).collect())
+        .unwrap_or_default();
+    let missing_roots: Vec<String> = roots.extra_roots.iter()
+        .filter(|root| !root.is_dir())
+        .map(|root| root.to_string_lossy().into_owned())
+        .collect();
+    let cache_found = !found_versions.is_empty() || missing_roots.len() < roots.extra_roots.len();
+
+    CacheDiscovery { found_versions, missing_versions, missing_roots, cache_found }
+}
+
+#[tauri::command]
+async fn preview_clean_all(
+    settings: State<'_, SettingsState>
+) -> Result<CleanAllPreview, ()> { // Always returns an Ok, unreadable folders just don't count towards either total
+    let roots = settings.snapshot().await;
+    let pinned = settings.pinned().await;
+
+    // Walking every project is slow, keep it off the async runtime
+    let sized = tauri::async_runtime::spawn_blocking(move || {
+        find_project_folders(&roots, &mut Vec::new())
+            .par_iter()
+            .map(|project_folder| {
+                let protected = pinned.contains(&project_id(&project_folder.path)) || project_in_use(&project_folder.path);
+                (folder_size(&project_folder.path), protected)
+            })
+            .collect::<Vec<(u64, bool)>>()
+    }).await.unwrap_or_default();
+
+    let totals = |protected: bool| {
+        let matching = sized.iter().filter(|(_, is_protected)| *is_protected == protected);
+        CacheTotals {
+            total_bytes: matching.clone().map(|(size, _)| size).sum(),
+            project_count: matching.count(),
+        }
+    };
+
+    Ok(CleanAllPreview { reclaimable: totals(false), protected: totals(true) })
+}
+
+#[tauri::command]
+async fn estimate_scan_cost(
+    settings: State<'_, SettingsState>
+) -> Result<ScanCost, ()> { // Always returns an Ok, unreadable folders just don't count
+    let roots = settings.snapshot().await;
+
+    // Only lists the folders, none of the projects get walked, but a network share can still be slow to list
+    let cost = tauri::async_runtime::spawn_blocking(move || {
+        let project_count = find_project_folders(&roots, &mut Vec::new()).len();
+        let on_network = roots.revit_cc_base.iter().chain(roots.extra_roots.iter()).any(|root| is_network_path(root));
+        ScanCost { project_count, on_network }
+    }).await.unwrap_or_default();
+
+    Ok(cost)
+}
+
+#[tauri::command]
+async fn get_total_cache_size(
+    settings: State<'_, SettingsState>
+) -> Result<CacheTotals, ()> { // Always returns an Ok, unreadable folders just don't count towards the total
+    let roots = settings.snapshot().await;
+
+    // Every project gets walked, so keep it off the async runtime
+    Ok(tauri::async_runtime::spawn_blocking(move || total_cache_size(&roots)).await.unwrap_or_default())
+}
+
+// Same traversal as get_projects, but only the sizes are kept
+fn total_cache_size(roots: &ScanRoots) -> CacheTotals {
+    let project_folders_found = find_project_folders(roots, &mut Vec::new());
+    let total_bytes = project_folders_found
+        .par_iter()
+        .map(|project_folder| folder_size(&project_folder.path))
+        .sum();
+
+    CacheTotals {
+        total_bytes,
+        project_count: project_folders_found.len(),
+    }
+}
+
+#[tauri::command]
+async fn start_auto_refresh(
+    interval_secs: u64,
+    auto_refresh: State<'_, AutoRefresh>,
+    app: AppHandle
+) -> Result<(), AppError> { // Does not return an Ok, only returns an Err if there is an issue
+    if interval_secs < MIN_AUTO_REFRESH_SECS {
+        return Err(AppError::Invalid(format!("The refresh interval has to be at least {} seconds.", MIN_AUTO_REFRESH_SECS)));
+    }
+
+    // Only ever have one timer going, starting again just swaps the interval
+    let mut task = auto_refresh.0.lock().await;
+    if let Some(previous) = task.take() {
+        previous.abort();
+    }
+
+    info!(interval_secs, "Starting auto refresh");
+    *task = Some(tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(interval_secs));
+        // Each check is awaited before the next tick is taken, so a slow one just pushes the next back rather than
+        // letting two run at once
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        let mut last_totals: Option<CacheTotals> = None;
+
+        loop {
+            interval.tick().await;
+
+            let roots = app.state::<SettingsState>().snapshot().await;
+            let Ok(totals) = tauri::async_runtime::spawn_blocking(move || total_cache_size(&roots)).await else {
+                continue;
+            };
+
+            // Only tell the frontend about changes big enough to matter, the first check just sets the baseline
+            let changed = last_totals.as_ref().is_some_and(|last| {
+                last.project_count != totals.project_count
+                    || last.total_bytes.abs_diff(totals.total_bytes) >= AUTO_REFRESH_MIN_CHANGE
+            });
+            if changed {
+                debug!(total_bytes = totals.total_bytes, projects = totals.project_count, "Cache totals changed");
+                let _ = app.emit("cache-totals-changed", &totals);
+            }
+            if changed || last_totals.is_none() {
+                last_totals = Some(totals);
+            }
+        }
+    }));
+
+    Ok(())
+}
+
+#[tauri::command]
+async fn stop_auto_refresh(
+    auto_refresh: State<'_, AutoRefresh>
+) -> Result<(), ()> { // Always returns an Ok, stopping when nothing is running does nothing
+    if let Some(task) = auto_refresh.0.lock().await.take() {
+        info!("Stopping auto refresh");
+        task.abort();
+    }
+    Ok(())
+}
+
+// Finds the total and available space on the volume a path lives on
+// The volume a (canonical) path is on is the disk with the longest mount point that contains it
+fn find_disk<'a>(disks: &'a sysinfo::Disks, path: &Path) -> Option<&'a sysinfo::Disk> {
+    disks.list()
+        .iter()
+        .filter(|disk| path.starts_with(disk.mount_point()))
+        .max_by_key(|disk| disk.mount_point().as_os_str().len())
+}
+
+// Whether a path is on a network share, either written as one or a mapped drive letter, which canonicalizes to the
+// share it's mapped to
+fn is_network_path(path: &Path) -> bool {
+    use std::path::{Component, Prefix};
+
+    let is_unc = |path: &Path| matches!(
+        path.components().next(),
+        Some(Component::Prefix(prefix)) if matches!(prefix.kind(), Prefix::UNC(..) | Prefix::VerbatimUNC(..))
+    );
+    is_unc(path) || fs::canonicalize(path).is_ok_and(|canonical| is_unc(&canonical))
+}
+
+fn disk_space_for(path: &Path) -> Result<DiskSpace, AppError> {
+    // Resolve the real path so it can be matched against the mount points, dunce keeps it in the C:\ form rather than \\?\C:\
+    let path = dunce::canonicalize(path)
+        .map_err(|e| AppError::PathMissing(format!("Could not find the Revit cache directory {:?}: {}", path, e)))?;
+
+    let disks = sysinfo::Disks::new_with_refreshed_list();
+    find_disk(&disks, &path)
+        .map(|disk| DiskSpace {
+            total_bytes: disk.total_space(),
+            available_bytes: disk.available_space(),
+        })
+        .ok_or_else(|| AppError::PathMissing(format!("Could not find the drive containing {:?}", path)))
+}
+
+#[tauri::command]
+async fn get_disk_space(
+    settings: State<'_, SettingsState>
+) -> Result<DiskSpace, AppError> {
+    let roots = settings.snapshot().await;
+    disk_space_for(roots.base()?)
+}
+
+// Works out the free space again in the background and sends it as "disk-space-updated", so a delete can show what it
+// freed without waiting on this. Nothing is sent if it can't be worked out, get_disk_space will say why.
+fn emit_disk_space(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let roots = app.state::<SettingsState>().snapshot().await;
+        let disk_space = tauri::async_runtime::spawn_blocking(move || disk_space_for(roots.base()?)).await;
+        match disk_space {
+            Ok(Ok(disk_space)) => {
+                let _ = app.emit("disk-space-updated", disk_space);
+            }
+            Ok(Err(e)) => debug!("Couldn't refresh the disk space after a delete: {}", e),
+            Err(e) => debug!("Disk space refresh stopped unexpectedly: {}", e),
+        }
+    });
+}
+
+// Checks if a walk entry is a subfolder the caller asked to leave out, the project folder itself is never excluded
+fn is_excluded_folder(entry: &walkdir::DirEntry, excluded_folders: &[String]) -> bool {
+    if entry.depth() == 0 || !entry.file_type().is_dir() {
+        return false;
+    }
+
+    let name = entry.file_name().to_string_lossy().to_lowercase();
+    excluded_folders.iter().any(|excluded| excluded.to_lowercase() == name)
+}
+
+// Whether a walk error is only because the entry was deleted between being listed and being read
+fn is_vanished(error: &walkdir::Error) -> bool {
+    error.io_error().is_some_and(|e| e.kind() == std::io::ErrorKind::NotFound)
+}
+
+// What a file really takes up on disk, from the allocation Windows keeps for it. That's rounded up to whole clusters, so
+// a cache full of tiny files takes more than its files add up to. The standard allocation size is the space NTFS set
+// aside and doesn't shrink for compressed or sparse files, those are asked for the size actually in use instead.
+#[cfg(windows)]
+fn allocated_size(path: &Path, metadata: &fs::Metadata) -> Option<u64> {
+    use std::os::windows::ffi::OsStrExt;
+    use std::os::windows::fs::{MetadataExt, OpenOptionsExt};
+    use std::os::windows::io::AsRawHandle;
+
+    // FILE_STANDARD_INFO, only the allocation size gets read
+    #[repr(C)]
+    struct FileStandardInfo {
+        allocation_size: i64,
+        _end_of_file: i64,
+        _number_of_links: u32,
+        _delete_pending: u8,
+        _directory: u8,
+    }
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn GetFileInformationByHandleEx(file: *mut std::ffi::c_void, class: i32, info: *mut std::ffi::c_void, size: u32) -> i32;
+        fn GetCompressedFileSizeW(file_name: *const u16, file_size_high: *mut u32) -> u32;
+    }
+
+    const FILE_STANDARD_INFO_CLASS: i32 = 1;
+    const FILE_READ_ATTRIBUTES: u32 = 0x80;
+    const FILE_SHARE_ALL: u32 = 0x1 | 0x2 | 0x4; // Read, write and delete, so a file Revit has open can still be sized
+    const FILE_ATTRIBUTE_SPARSE_FILE: u32 = 0x200;
+    const FILE_ATTRIBUTE_COMPRESSED: u32 = 0x800;
+    const INVALID_FILE_SIZE: u32 = u32::MAX;
+
+    // Compressed and sparse files are rare in a cache, so only they pay for the extra lookup by path
+    if metadata.file_attributes() & (FILE_ATTRIBUTE_SPARSE_FILE | FILE_ATTRIBUTE_COMPRESSED) != 0 {
+        let wide: Vec<u16> = long_path(path).as_os_str().encode_wide().chain(std::iter::once(0)).collect();
+        let mut high = 0u32;
+
+        // Safe since the name is nul terminated and high outlives the call. INVALID_FILE_SIZE is also a real low half,
+        // so it only means a failure when the last error says so.
+        let low = unsafe { GetCompressedFileSizeW(wide.as_ptr(), &mut high) };
+        if low == INVALID_FILE_SIZE && std::io::Error::last_os_error().raw_os_error() != Some(0) {
+            return None;
+        }
+        return Some((u64::from(high) << 32) | u64::from(low));
+    }
+
+    let file = fs::OpenOptions::new()
+        .access_mode(FILE_READ_ATTRIBUTES)
+        .share_mode(FILE_SHARE_ALL)
+        .open(path)
+        .ok()?;
+    let mut info = std::mem::MaybeUninit::<FileStandardInfo>::zeroed();
+    let size = std::mem::size_of::<FileStandardInfo>() as u32;
+
+    // Safe since the handle stays open for the call and the buffer is the size we say it is
+    let read = unsafe { GetFileInformationByHandleEx(file.as_raw_handle(), FILE_STANDARD_INFO_CLASS, info.as_mut_ptr().cast(), size) };
+    if read == 0 {
+        return None;
+    }
+    let info = unsafe { info.assume_init() };
+    u64::try_from(info.allocation_size).ok()
+}
+
+// Everywhere else the metadata already has the blocks the file takes up, always counted in 512 byte units
+#[cfg(unix)]
+fn allocated_size(_path: &Path, metadata: &fs::Metadata) -> Option<u64> {
+    use std::os::unix::fs::MetadataExt;
+
+    Some(metadata.blocks() * 512)
+}
+
+#[cfg(not(any(windows, unix)))]
+fn allocated_size(_path: &Path, _metadata: &fs::Metadata) -> Option<u64> {
+    None
+}
+
+// Whether a folder right under a project is one of its cached revisions. Revit names those by a GUID or a number, so
+// anything else sitting there (like the model's <name>_backup folder) is neither counted nor purged as a revision.
+fn is_revision_folder(name: &std::ffi::OsStr) -> bool {
+    let name = name.to_string_lossy();
+    metadata::is_guid(&name) || (!name.is_empty() && name.chars().all(|c| c.is_ascii_digit()))
+}
+
+// Every revision folder in a project except the newest, going by the files inside them
+fn old_revisions(project_path: &Path) -> std::io::Result<Vec<PathBuf>> {
+    let mut revisions: Vec<(PathBuf, Option<std::time::SystemTime>)> = fs::read_dir(project_path)?
+        .flatten()
+        .filter(|entry| entry.file_type().is_ok_and(|file_type| file_type.is_dir()) && is_revision_folder(&entry.file_name()))
+        .map(|entry| {
+            let revision = entry.path();
+            let newest = newest_modified(&revision);
+            (revision, newest)
+        })
+        .collect();
+    revisions.sort_by_key(|(_, newest)| Reverse(*newest));
+
+    Ok(revisions.into_iter().skip(1).map(|(revision, _)| revision).collect())
+}
+
+// Everything a walk of a project folder adds up. Scans, rescans and the measuring commands all go through walk_project
+// so they can't drift apart in what they count.
+#[derive(Debug, Default)]
+struct ProjectWalk {
+    total_size: u64,
+    on_disk_size: u64,
+    file_count: u64,
+    revision_count: u64,
+    dirs_visited: u64, // Every folder the walk went into, including the project folder itself
+    depth_capped: bool, // There were folders past max_depth, so the totals are probably short
+    youngest: Option<std::time::SystemTime>, // Newest modified time of any file
+    oldest: Option<std::time::SystemTime>,
+    warnings: Vec<ScanWarning>,
+}
+
+// Walks a project folder for its totals and file times, giving up with None if the scan is stopped partway through
+fn walk_project(path: &Path, options: &ScanOptions, stop: &ScanStop) -> Option<ProjectWalk> {
+    // Set up container variables for the size and creation date of the newest file
+    let mut total_size: u64 = 0;
+    let mut on_disk_size: u64 = 0;
+    let mut file_count: u64 = 0;
+    let mut revision_count: u64 = 0;
+    let mut dirs_visited: u64 = 0;
+    let mut depth_capped = false;
+    let mut youngest_file_time: Option<std::time::SystemTime> = None;
+    let mut oldest_file_time: Option<std::time::SystemTime> = None;
+    let mut walk_warnings = Vec::new();
+
+    // Walk the directory for each file/folder, skipping over any excluded subfolders entirely so they count towards
+    // neither the size nor the age. When following links walkdir notices a link back up the tree and hands us an error
+    // for it instead of going round forever, which just ends up as a warning below.
+    let walker = WalkDir::new(long_path(path))
+        .follow_links(options.follow_links)
+        .max_depth(options.max_depth)
+        .into_iter()
+        .filter_entry(|entry| !is_excluded_folder(entry, &options.excluded_folders));
+    for (index, entry) in walker.enumerate() {
+        // Every so often check if the scan was cancelled or timed out, and drop this partially walked project if it was
+        if index % CANCEL_CHECK_INTERVAL == 0 && stop.should_stop() {
+            return None;
+        }
+
+        // Skip any entries we're not able to get, but keep a note of them. Revit rewrites the cache while it syncs, so
+        // anything that disappears partway through is just skipped since it's no longer taking up space anyway.
+        let found = match entry.and_then(|entry| entry.metadata().map(|metadata| (entry, metadata))) {
+            Ok(found) => Some(found),
+            Err(e) if is_vanished(&e) => {
+                debug!(path = ?e.path(), "Entry vanished mid-scan");
+                None
+            }
+            Err(e) => {
+                let failed_path = e.path().unwrap_or(path).to_path_buf();
+                walk_warnings.push(ScanWarning::new(&failed_path, e));
+                None
+            }
+        };
+
+        if let Some((entry, metadata)) = found {
+            // Folders right under the project are its revisions, as long as they're named like one
+            if entry.depth() == 1 && metadata.is_dir() && is_revision_folder(entry.file_name()) {
+                revision_count += 1;
+            }
+            if metadata.is_dir() {
+                dirs_visited += 1;
+
+                // A folder right at the limit isn't walked into, so whatever is inside it is missing from the totals
+                let has_entries = || fs::read_dir(entry.path()).is_ok_and(|mut entries| entries.next().is_some());
+                if entry.depth() == options.max_depth && !depth_capped && has_entries() {
+                    debug!(path = ?entry.path(), "Hit the walk depth limit");
+                    depth_capped = true;
+                }
+            }
+
+            // Check the current entry is a file (i.e. not a folder)
+            if !metadata.is_file() {
+                continue;
+            }
+
+            // Add the size of the current entry to the total size for the project
+            total_size += metadata.len();
+            file_count += 1;
+
+            // Also add what the file actually takes up on disk, rounded up to whole clusters and less for compressed
+            // files. Fall back to the logical size if the filesystem won't tell us.
+            on_disk_size += allocated_size(entry.path(), &metadata).unwrap_or(metadata.len());
+
+            // Check that the entry has a modified time
+            if let Ok(modified_time) = metadata.modified() {
+                // Check if the new time is younger than the current youngest time
+                match youngest_file_time {
+                    Some(current_youngest) if modified_time > current_youngest => {
+                        youngest_file_time = Some(modified_time);
+                    }
+                    None => {
+                        youngest_file_time = Some(modified_time);
+                    }
+                    // Do nothing if its not
+                    _ => {}
+                }
+
+                // Same again for the oldest time
+                match oldest_file_time {
+                    Some(current_oldest) if modified_time < current_oldest => {
+                        oldest_file_time = Some(modified_time);
+                    }
+                    None => {
+                        oldest_file_time = Some(modified_time);
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    Some(ProjectWalk {
+        total_size,
+        on_disk_size,
+        file_count,
+        revision_count,
+        dirs_visited,
+        depth_capped,
+        youngest: youngest_file_time,
+        oldest: oldest_file_time,
+        warnings: walk_warnings,
+    })
+}
+
+// What walking a project turned up besides the project itself, for the warnings list and the scan timings
+struct WalkStats {
+    warnings: Vec<ScanWarning>,
+    dirs_visited: u64,
+    elapsed: Duration,
+    youngest: Option<std::time::SystemTime>, // Only the days since make it onto the project, which is too coarse to rank by
+}
+
+// Checks every file under a folder is empty, with no exclusions or depth limit. Anything that can't be read counts as
+// data, so this only says yes when it's certain.
+fn holds_no_data(path: &Path) -> bool {
+    WalkDir::new(long_path(path)).into_iter().all(|entry| {
+        entry.and_then(|entry| entry.metadata()).is_ok_and(|metadata| !metadata.is_file() || metadata.len() == 0)
+    })
+}
+
+// Walks one project folder and builds its definition, giving up with None if the scan is stopped partway through
+fn scan_project_folder(
+    folder: &ProjectFolder,
+    options: &ScanOptions,
+    stop: &ScanStop,
+    names: &NameCache
+) -> Option<(ProjectDef, WalkStats)> {
+    let ProjectFolder { year: vers, path: project_folder_path, name: project_name, root } = folder;
+    let started = Instant::now();
+
+    // Make an id for the project from its path
+    let path_id = project_id(project_folder_path);
+
+    // Walk the folder, dropping it if the scan gets stopped partway through
+    let ProjectWalk {
+        total_size,
+        on_disk_size,
+        file_count,
+        revision_count,
+        dirs_visited,
+        depth_capped,
+        youngest: youngest_file_time,
+        oldest: oldest_file_time,
+        warnings: walk_warnings,
+    } = walk_project(project_folder_path, options, stop)?;
+
+    // Convert the system times into a number of days
+    let days_old = days_since(youngest_file_time);
+    let oldest_days_old = days_since(oldest_file_time);
+
+    // File times move whenever anything in the cache is written, the session log is a better sign the model was opened
+    let last_opened = metadata::last_opened(project_folder_path).or(youngest_file_time).map(unix_seconds);
+
+    // Work out what model this cache belongs to, using the friendlier name when we can find one
+    // Only the displayed name gets converted, so an odd name can't change the id or the path we delete. Reading the
+    // metadata means opening files, so a saved name is used if nothing it came from has changed since.
+    let folder_name = project_name.to_string_lossy();
+    let project_metadata = names.resolve(&path_id, project_folder_path, &folder_name);
+
+    // Set up the definition for the project to pass to the frontend
+    let new_project = ProjectDef {
+        id: path_id,
+        name: project_metadata.display_name.unwrap_or_else(|| folder_name.into_owned()),
+        year: *vers,
+        size: total_size,
+        size_display: format_bytes(total_size),
+        on_disk_size,
+        days: days_old,
+        oldest_days: oldest_days_old,
+        last_opened,
+        file_count,
+        revision_count,
+        user_folder: project_folder_path.parent()
+            .and_then(Path::file_name)
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default(),
+        central_guid: project_metadata.central_guid,
+        server_hint: project_metadata.server_hint,
+        root: root.to_string_lossy().into_owned(),
+        path_display: dunce::simplified(project_folder_path).to_string_lossy().into_owned(),
+        // If nothing has been touched in a long time the central model has most likely been deleted or archived
+        orphaned: days_old >= options.orphan_days,
+        // Pins live in app state, the caller fills this in
+        pinned: false,
+        in_use: project_in_use(project_folder_path),
+        score: staleness_score(total_size, days_old),
+        // The walk above leaves out excluded folders and stops at the depth limit, so a zero there isn't enough to call
+        // it empty. Anything it couldn't read might have data in it too.
+        empty: total_size == 0 && !depth_capped && walk_warnings.is_empty() && holds_no_data(project_folder_path),
+        depth_capped,
+    };
+
+    let walk_stats = WalkStats { warnings: walk_warnings, dirs_visited, elapsed: started.elapsed(), youngest: youngest_file_time };
+    Some((new_project, walk_stats))
+}
+
+// Ranks a project by how worth deleting it is, the size in SCORE_SIZE_UNIT weighted by the log of how many days it's
+// been untouched. The log means age still counts for a lot early on but a huge project can't be outranked just by being
+// ancient.
+fn staleness_score(size: u64, days: u64) -> f64 {
+    let size_units = size as f64 / SCORE_SIZE_UNIT;
+    let age_weight = (days as f64 + SCORE_AGE_OFFSET_DAYS).ln() * SCORE_AGE_FACTOR;
+    size_units * age_weight
+}
+
+#[tauri::command]
+async fn get_installed_versions(
+    include_size: Option<bool>, // Walk each version's folder for its size too, off by default since it's slow on a big cache
+    settings: State<'_, SettingsState>
+) -> Result<Vec<InstalledVersion>, ()> { // Always returns an Ok, versions without a cache folder are just left out
+    let roots = settings.snapshot().await;
+
+    // Without a base path there's nothing installed that we can see
+    let Some(revit_cc_base) = roots.revit_cc_base.clone() else {
+        return Ok(Vec::new());
+    };
+
+    // Only keep the years that actually have a CollaborationCache folder
+    let years: Vec<u16> = roots.installed_years()
+        .into_iter()
+        .filter(|vers| version_cache_path(&revit_cc_base, *vers).is_dir())
+        .collect();
+
+    // Listing the user folders is enough to count the projects, only the size needs the full walk
+    let include_size = include_size.unwrap_or(false);
+    let versions = tauri::async_runtime::spawn_blocking(move || {
+        years
+            .into_par_iter()
+            .map(|year| {
+                let vers_path = version_cache_path(&revit_cc_base, year);
+                let mut project_folders = Vec::new();
+                find_root_project_folders(&vers_path, year, &mut Vec::new(), &mut project_folders);
+
+                InstalledVersion {
+                    year,
+                    project_count: project_folders.len(),
+                    size: include_size.then(|| folder_size(&vers_path)),
+                }
+            })
+            .collect()
+    }).await.unwrap_or_default();
+
+    Ok(versions)
+}
+
+#[tauri::command]
+async fn get_projects(
+    options: Option<ScanOptions>, // Sorting, filtering and thresholds for the scan, all optional
+    use_cached: Option<bool>, // Return the saved list straight away and rescan in the background
+    last_scan: State<'_, LastScan>,
+    settings: State<'_, SettingsState>,
+    app: AppHandle
+) -> Result<ScanResult, AppError> { // Result is eiter the projects and any warnings on Ok, or an error if there's no base path or a scan is already running.
+    let options = settings.scan_options(options).await;
+
+    // Without a base path the scan would just come back empty, which looks like there's nothing to clean
+    let roots = settings.snapshot().await;
+    roots.base()?;
+
+    // If we've been asked for the cached list and have one, hand it back now and check it against a fresh walk afterwards
+    if use_cached.unwrap_or(false) {
+        let cached = last_scan.projects.lock().await.clone();
+        if !cached.is_empty() {
+            let background_app = app.clone();
+            let previous = cached.clone();
+            tauri::async_runtime::spawn(async move {
+                // If another scan got in first there's nothing to check, it'll hand back its own results
+                let Ok(fresh) = scan_projects(&background_app, &options, false).await else {
+                    return;
+                };
+
+                // Only bother the frontend if something actually changed
+                if fresh.projects != previous || !fresh.warnings.is_empty() {
+                    let _ = background_app.emit("projects-updated", fresh);
+                }
+            });
+            return Ok(ScanResult { projects: cached, warnings: Vec::new(), truncated: false, discovery: discover_cache(&roots), timings: None, most_recent: None });
+        }
+    }
+
+    scan_projects(&app, &options, false).await
+}
+
+#[tauri::command]
+async fn get_last_results(
+    last_scan: State<'_, LastScan>
+) -> Result<LastResults, ()> { // Always returns an Ok, the list is empty if nothing has been scanned yet
+    let projects = last_scan.projects.lock().await.clone();
+    let ScanStamp { scanned_at, stale } = *last_scan.stamp.lock().await;
+    Ok(LastResults { projects, scanned_at, stale })
+}
+
+#[tauri::command]
+async fn get_projects_stream(
+    options: Option<ScanOptions>, // Same as get_projects
+    settings: State<'_, SettingsState>,
+    app: AppHandle
+) -> Result<Vec<ScanWarning>, AppError> { // The projects arrive as "project-found" events, this only returns the warnings once done
+    let options = settings.scan_options(options).await;
+    settings.snapshot().await.base()?;
+
+    let ScanResult { warnings, .. } = scan_projects(&app, &options, true).await?;
+    Ok(warnings)
+}
+
+#[tauri::command]
+async fn get_projects_grouped(
+    options: Option<ScanOptions>, // Same as get_projects, the sort applies to the projects within each version
+    settings: State<'_, SettingsState>,
+    app: AppHandle
+) -> Result<GroupedScanResult, AppError> { // Result is eiter the grouped projects and any warnings on Ok, or an error if there's no base path or a scan is already running.
+    let options = settings.scan_options(options).await;
+    settings.snapshot().await.base()?;
+    let ScanResult { projects, warnings, truncated, discovery, .. } = scan_projects(&app, &options, false).await?;
+
+    // Bucket the projects by year, only years that actually had projects end up in the map
+    let mut versions: BTreeMap<u16, ProjectGroup> = BTreeMap::new();
+    for project in projects {
+        versions.entry(project.year).or_default().push(project);
+    }
+
+    Ok(GroupedScanResult { versions, warnings, truncated, discovery })
+}
+
+#[tauri::command]
+async fn get_projects_by_user(
+    options: Option<ScanOptions>, // Same as get_projects, the sort applies to the projects within each user folder
+    settings: State<'_, SettingsState>,
+    app: AppHandle
+) -> Result<UserGroupedScanResult, AppError> { // Result is eiter the grouped projects and any warnings on Ok, or an error if there's no base path or a scan is already running.
+    let options = settings.scan_options(options).await;
+    settings.snapshot().await.base()?;
+    let ScanResult { projects, warnings, truncated, discovery, .. } = scan_projects(&app, &options, false).await?;
+
+    // Bucket the projects by the user folder they sit in, which is how shared machines keep each profile's caches apart
+    let mut users: BTreeMap<String, ProjectGroup> = BTreeMap::new();
+    for project in projects {
+        users.entry(project.user_folder.clone()).or_default().push(project);
+    }
+
+    Ok(UserGroupedScanResult { users, warnings, truncated, discovery })
+}
+
+// Checks a project against the size and age thresholds of a scan
+fn passes_filters(project: &ProjectDef, options: &ScanOptions) -> bool {
+    !(options.min_size_bytes.is_some_and(|min| project.size < min) || options.min_days.is_some_and(|min| project.days < min))
+}
+
+#[tauri::command]
+async fn get_filtered_total(
+    min_size_bytes: Option<u64>, // Same as the scan thresholds, leave either out to not filter on it
+    min_days: Option<u64>,
+    last_scan: State<'_, LastScan>
+) -> Result<CacheTotals, ()> { // Always returns an Ok, comes from the last scan so nothing gets walked
+    let options = ScanOptions { min_size_bytes, min_days, ..Default::default() };
+    let projects = last_scan.projects.lock().await;
+    let matching = projects.iter().filter(|project| passes_filters(project, &options));
+
+    let (total_bytes, project_count) = matching.fold((0, 0), |(bytes, count), project| (bytes + project.size, count + 1));
+    Ok(CacheTotals { total_bytes, project_count })
+}
+
+#[tauri::command]
+async fn get_version_savings(
+    last_scan: State<'_, LastScan>
+) -> Result<BTreeMap<u16, CacheTotals>, ()> { // Always returns an Ok, comes from the last scan so nothing gets walked
+    let mut savings: BTreeMap<u16, CacheTotals> = BTreeMap::new();
+
+    // Pinned projects and ones open in Revit wouldn't be deleted, so they don't count as reclaimable
+    for project in last_scan.projects.lock().await.iter().filter(|project| !project.pinned && !project.in_use) {
+        let totals = savings.entry(project.year).or_insert(CacheTotals { total_bytes: 0, project_count: 0 });
+        totals.total_bytes += project.size;
+        totals.project_count += 1;
+    }
+
+    Ok(savings)
+}
+
+#[tauri::command]
+async fn get_age_histogram(
+    bucket_ends: Option<Vec<u64>>, // The last day of each bucket but the last, which takes everything older. Defaults to 7, 30, 90
+    last_scan: State<'_, LastScan>
+) -> Result<Vec<AgeBucket>, AppError> { // Returns one bucket per range on Ok, or an Err if the ends aren't in order
+    let bucket_ends = bucket_ends.unwrap_or_else(|| DEFAULT_AGE_BUCKET_ENDS.to_vec());
+    if bucket_ends.windows(2).any(|pair| pair[0] >= pair[1]) {
+        return Err(AppError::Invalid("Age buckets have to go from youngest to oldest without repeating.".to_string()));
+    }
+
+    // Each bucket starts the day after the one before it ends
+    let mut buckets: Vec<AgeBucket> = std::iter::once(0).chain(bucket_ends.iter().map(|end| end + 1))
+        .zip(bucket_ends.iter().map(|end| Some(*end)).chain(std::iter::once(None)))
+        .map(|(min_days, max_days)| AgeBucket { min_days, max_days, totals: CacheTotals { total_bytes: 0, project_count: 0 } })
+        .collect();
+
+    // Comes from the last scan so nothing gets walked
+    for project in last_scan.projects.lock().await.iter() {
+        let index = bucket_ends.iter().position(|end| project.days <= *end).unwrap_or(bucket_ends.len());
+        buckets[index].totals.total_bytes += project.size;
+        buckets[index].totals.project_count += 1;
+    }
+
+    Ok(buckets)
+}
+
+#[tauri::command]
+async fn compare_projects(
+    first_id: String,
+    second_id: String,
+    full: Option<bool>, // Read every byte rather than sampling, slow on a big cache but certain
+    cache: State<'_, ProjectCache>
+) -> Result<ContentComparison, AppError> { // Returns how the two compare on Ok, or an Err if either can't be read
+    let (first_path, second_path) = {
+        let cache_guard = cache.0.lock().await;
+        let lookup = |id: &str| cache_guard.get(id).cloned().ok_or_else(|| AppError::not_in_cache(id));
+        (lookup(&first_id)?, lookup(&second_id)?)
+    };
+
+    // Both get hashed at the same time, there's no point waiting on one to finish before starting the other
+    let full = full.unwrap_or(false);
+    let (first, second) = rayon::join(
+        || hash::fingerprint(&long_path(&first_path), full).map_err(|e| AppError::io(&first_path, e)),
+        || hash::fingerprint(&long_path(&second_path), full).map_err(|e| AppError::io(&second_path, e)),
+    );
+    let (first, second) = (first?, second?);
+    info!(first = %first_id, second = %second_id, full, identical = first == second, "Compared projects");
+
+    Ok(ContentComparison {
+        identical: first == second,
+        full,
+        first_hash: first.hash,
+        second_hash: second.hash,
+        first_file_count: first.file_count,
+        second_file_count: second.file_count,
+    })
+}
+
+#[tauri::command]
+async fn get_duplicate_projects(
+    last_scan: State<'_, LastScan>
+) -> Result<Vec<DuplicateGroup>, ()> { // Always returns an Ok, groups come back biggest first
+    // Bucket the last scan by central model, only folders actually named by a GUID can be matched up
+    let mut by_guid: HashMap<String, Vec<ProjectDef>> = HashMap::new();
+    for project in last_scan.projects.lock().await.iter() {
+        if metadata::is_guid(&project.central_guid) {
+            by_guid.entry(project.central_guid.clone()).or_default().push(project.clone());
+        }
+    }
+
+    // A model only counts as duplicated if it's cached under more than one version
+    let mut duplicates: Vec<DuplicateGroup> = by_guid
+        .into_iter()
+        .filter(|(_, projects)| projects.iter().any(|project| project.year != projects[0].year))
+        .map(|(central_guid, mut projects)| {
+            projects.sort_by_key(|project| Reverse(project.year));
+            DuplicateGroup {
+                total_size: projects.iter().map(|project| project.size).sum(),
+                central_guid,
+                projects,
+            }
+        })
+        .collect();
+    duplicates.sort_by_key(|group| Reverse(group.total_size));
+
+    Ok(duplicates)
+}
+
+#[tauri::command]
+async fn search_projects(
+    query: String,
+    fuzzy: Option<bool>, // Also match when the letters of the query just appear in order, e.g. "hsptl" for "Hospital"
+    last_scan: State<'_, LastScan>
+) -> Result<Vec<ProjectDef>, ()> { // Always returns an Ok, closer matches come first
+    let query = query.trim().to_lowercase();
+    let projects = last_scan.projects.lock().await;
+    if query.is_empty() {
+        return Ok(projects.clone());
+    }
+
+    // Score each project on its best matching field, keeping the last scan's order for equally good matches
+    let fuzzy = fuzzy.unwrap_or(false);
+    let mut matches: Vec<(MatchQuality, &ProjectDef)> = projects.iter()
+        .filter_map(|project| {
+            [&project.name, &project.central_guid].into_iter()
+                .filter_map(|field| match_quality(&query, &field.to_lowercase(), fuzzy))
+                .min()
+                .map(|quality| (quality, project))
+        })
+        .collect();
+    matches.sort_by_key(|(quality, _)| *quality);
+
+    Ok(matches.into_iter().map(|(_, project)| project.clone()).collect())
+}
+
+// How well a search query matched, better matches sort first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum MatchQuality {
+    Prefix,
+    Substring,
+    Fuzzy,
+}
+
+// Checks a lowercased query against some lowercased text
+fn match_quality(query: &str, text: &str, fuzzy: bool) -> Option<MatchQuality> {
+    if text.starts_with(query) {
+        return Some(MatchQuality::Prefix);
+    }
+    if text.contains(query) {
+        return Some(MatchQuality::Substring);
+    }
+
+    // Fuzzy just needs every character of the query to turn up in the text in the same order
+    let mut text_chars = text.chars();
+    (fuzzy && query.chars().all(|query_char| text_chars.any(|text_char| text_char == query_char)))
+        .then_some(MatchQuality::Fuzzy)
+}
+
+// Sorts a scanned list, sort_by and sort_by_key are stable so ties keep the order they were found in
+fn sort_projects(projects: &mut [ProjectDef], sort: ProjectSort) {
+    match sort {
+        ProjectSort::ScoreDesc => projects.sort_by(|a, b| b.score.total_cmp(&a.score)),
+        ProjectSort::SizeDesc => projects.sort_by_key(|prj| Reverse(prj.size)),
+        ProjectSort::AgeDesc => projects.sort_by_key(|prj| Reverse(prj.days)),
+        ProjectSort::NameAsc => projects.sort_by(|a, b| a.name.cmp(&b.name)),
+        ProjectSort::YearAsc => projects.sort_by_key(|prj| prj.year),
+    }
+}
+
+// Walks every version folder and builds the project list, refreshing the ProjectCache and LastScan along the way
+#[tracing::instrument(skip(app))]
+async fn scan_projects(
+    app: &AppHandle,
+    options: &ScanOptions,
+    stream: bool // Emit each project as "project-found" as soon as it's walked, rather than only returning them at the end
+) -> Result<ScanResult, AppError> {
+    options.validate()?;
+
+    // Only one scan at a time, a second one (say from a double-clicked refresh) is turned away
+    let scan_in_progress = app.state::<ScanInProgress>();
+    let _scan_guard = scan_in_progress.start()?;
+
+    // Pull the state we need off the app handle so this can also be run from a background task
+    let settings = app.state::<SettingsState>();
+    let cache = app.state::<ProjectCache>();
+    let cancel = app.state::<CancellationToken>();
+
+    // Container vectors for the finished projects and any folders we had to skip
+    let mut all_projects = Vec::new();
+    let mut warnings = Vec::new();
+
+    // Clear cache on run
+    cache.0.lock().await.clear();
+
+    // Grab the current base path, extra roots and version range, they may have been changed since the last scan
+    let roots = settings.snapshot().await;
+    info!(base = ?roots.revit_cc_base, extra_roots = roots.extra_roots.len(), versions = ?roots.version_range, "Starting scan");
+
+    // Reset the cancellation flag so a previous cancel doesn't stop this scan, and start the clock if there's a time limit
+    let cancelled = cancel.0.clone();
+    cancelled.store(false, Ordering::Relaxed);
+    let deadline = options.max_duration_secs.map(|secs| Instant::now() + Duration::from_secs(secs));
+
+    // Find every project folder under the base path, these get walked in parallel below
+    let scan_started = Instant::now();
+    let mut project_jobs = find_project_folders(&roots, &mut warnings);
+
+    // Anything waiting in the quarantine has been deleted as far as the user is concerned. Revit can put a fresh folder
+    // back at the same path before it's purged, which shouldn't pop the row back into the list until then.
+    let quarantined = app.state::<Quarantine>().original_paths().await;
+    project_jobs.retain(|folder| !quarantined.contains(&folder.path));
+    let find_ms = scan_started.elapsed().as_millis() as u64;
+
+    // Copy the pins and saved names out so the walk doesn't need the lock
+    let pinned = settings.pinned().await;
+    let names = NameCache::new(settings.resolved_names().await);
+
+    // Keep track of how many projects have been walked so we can report progress to the frontend
+    let total_jobs = project_jobs.len();
+
+    // Walk each project folder on the rayon thread pool, since the recursive walk is the expensive part. The whole walk
+    // can take a good while, so it runs off the async runtime with its own copies of everything it needs.
+    let walk_started = Instant::now();
+    let walk_app = app.clone();
+    let walk_options = options.clone();
+    let walk = tauri::async_runtime::spawn_blocking(move || {
+        let stop = ScanStop { cancelled: &cancelled, deadline };
+        let cache = walk_app.state::<ProjectCache>();
+        let walked = AtomicUsize::new(0);
+        let scanned: Vec<(ProjectDef, PathBuf, WalkStats)> = project_jobs
+            .into_par_iter()
+            .filter_map(|folder| {
+                // Skip the project entirely if the scan has been cancelled or run out of time
+                if stop.should_stop() {
+                    return None;
+                }
+
+                // Walk the folder, dropping it if the scan gets stopped partway through
+                let (mut new_project, walk_stats) = scan_project_folder(&folder, &walk_options, &stop, &names)?;
+                new_project.pinned = pinned.contains(&new_project.id);
+
+                // Let the frontend know how far along we are, emit failures are fine to ignore since nobody might be listening
+                let discovered = walked.fetch_add(1, Ordering::Relaxed) + 1;
+                let _ = walk_app.emit("scan-progress", ScanProgress {
+                    year: folder.year,
+                    discovered,
+                    percent: (discovered * 100 / total_jobs) as u8,
+                });
+
+                // Send the project straight over if we're streaming, it has to be in the cache first so the frontend can act
+                // on it right away. We're on a rayon thread here rather than in the async runtime, so blocking is fine.
+                if stream && passes_filters(&new_project, &walk_options) {
+                    cache.0.blocking_lock().insert(new_project.id.clone(), folder.path.clone());
+                    let _ = walk_app.emit("project-found", &new_project);
+                }
+
+                Some((new_project, folder.path, walk_stats))
+            })
+            .collect();
+        (scanned, walked.into_inner(), names)
+    }).await;
+    let (scanned, walked, names) = walk.map_err(|e| AppError::Io(format!("The scan stopped unexpectedly: {}", e)))?;
+    let walk_ms = walk_started.elapsed().as_millis() as u64;
+
+    // Lock the cache once and add every id:path pair to keep a referenceable pristine PathBuf copy of the path on the backend
+    let mut cache_guard = cache.0.lock().await;
+    let mut seen_ids = BTreeSet::new();
+    let mut timings = ScanTimings { find_ms, walk_ms, ..Default::default() };
+    let mut most_recent = None;
+    for (new_project, project_folder_path, walk_stats) in scanned {
+        // Keep the warnings and the counts even if the project itself gets filtered out
+        warnings.extend(walk_stats.warnings);
+        seen_ids.insert(new_project.id.clone());
+        timings.dirs_visited += walk_stats.dirs_visited;
+        timings.files_statted += new_project.file_count;
+        let walk_ms = walk_stats.elapsed.as_millis() as u64;
+        if timings.slowest_project.as_ref().is_none_or(|slowest| walk_ms > slowest.walk_ms) {
+            timings.slowest_project = Some(SlowestWalk { id: new_project.id.clone(), name: new_project.name.clone(), walk_ms });
+        }
+
+        // Skip any project that falls below either of the thresholds
+        if !passes_filters(&new_project, options) {
+            continue;
+        }
+
+        // Keep hold of whichever project was touched most recently, out of the ones that make it into the list
+        if let Some(youngest) = walk_stats.youngest {
+            let modified_at = unix_seconds(youngest);
+            if most_recent.as_ref().is_none_or(|recent: &RecentProject| modified_at > recent.modified_at) {
+                most_recent = Some(RecentProject { id: new_project.id.clone(), name: new_project.name.clone(), modified_at });
+            }
+        }
+
+        cache_guard.insert(new_project.id.clone(), project_folder_path);
+
+        // Push that definition onto the output vector
+        all_projects.push(new_project);
+    }
+
+    sort_projects(&mut all_projects, options.sort);
+
+    // Let go of the cache before anything else needs it
+    drop(cache_guard);
+
+    // Log everything that had to be skipped so there's something to go on when projects go missing
+    for scan_warning in &warnings {
+        warn!(path = %scan_warning.path, message = %scan_warning.message, "Skipped folder");
+    }
+    // Every walk that finished got counted, so anything short of the total was dropped by a cancel or the time limit
+    let truncated = walked < total_jobs;
+    timings.total_ms = scan_started.elapsed().as_millis() as u64;
+    info!(
+        projects = all_projects.len(), warnings = warnings.len(), truncated,
+        total_ms = timings.total_ms, dirs = timings.dirs_visited, files = timings.files_statted, "Scan finished"
+    );
+
+    // Save any names that had to be read fresh, only forgetting ones for missing projects if every project got walked.
+    // Filtered out projects still count as seen, they'll likely be back next time.
+    let seen = (!truncated).then_some(&seen_ids);
+    if let Err(e) = settings.save_resolved_names(names.into_fresh(), seen).await {
+        warn!("Couldn't save the resolved project names: {}", e);
+    }
+
+    // Let the frontend know the scan is done
+    let _ = app.emit("scan-complete", ScanComplete { total: all_projects.len(), timings: timings.clone() });
+
+    // Keep a copy of what we found so it can be saved on exit
+    let last_scan = app.state::<LastScan>();
+    *last_scan.projects.lock().await = all_projects.clone();
+    *last_scan.stamp.lock().await = ScanStamp {
+        scanned_at: Some(unix_seconds(std::time::SystemTime::now())),
+        stale: false,
+    };
+
+    Ok(ScanResult { projects: all_projects, warnings, truncated, discovery: discover_cache(&roots), timings: Some(timings), most_recent })
+}
+
+#[tauri::command]
+async fn rescan_project(
+    id: String,
+    options: Option<ScanOptions>, // Same as get_projects, only the orphan threshold and exclusions matter here
+    cache: State<'_, ProjectCache>,
+    last_scan: State<'_, LastScan>,
+    settings: State<'_, SettingsState>
+) -> Result<Option<ProjectDef>, AppError> { // Returns the refreshed project on Ok, or None if its folder is gone
+    // Try and pull the path from the id on the cache
+    let path = cache.0.lock().await.get(&id).cloned()
+        .ok_or_else(|| AppError::not_in_cache(&id))?;
+
+    // If the folder has been removed since the last scan, forget about it
+    if !path.is_dir() {
+        cache.0.lock().await.remove(&id);
+        last_scan.projects.lock().await.retain(|project| project.id != id);
+        return Ok(None);
+    }
+
+    // The version and root aren't stored with the path, so take them from the last scan
+    let (year, root) = last_scan.projects.lock().await.iter()
+        .find(|project| project.id == id)
+        .map(|project| (project.year, PathBuf::from(&project.root)))
+        .unwrap_or_default();
+    let folder = ProjectFolder {
+        year,
+        name: path.file_name().map(|name| name.to_os_string()).unwrap_or_default(),
+        root,
+        path,
+    };
+
+    // Re-walk just this one folder off the async runtime, there's nothing to cancel it so it always finishes
+    let options = settings.scan_options(options).await;
+    options.validate()?;
+    let names = NameCache::new(settings.resolved_names().await);
+    let (rescanned, names) = tauri::async_runtime::spawn_blocking(move || {
+        let rescanned = scan_project_folder(&folder, &options, &ScanStop::never(), &names);
+        (rescanned, names)
+    }).await.map_err(|e| AppError::Io(format!("Rescanning project with ID '{}' stopped unexpectedly: {}", id, e)))?;
+    let (mut refreshed, _) = rescanned
+        .ok_or_else(|| AppError::Io(format!("Failed to rescan project with ID '{}'.", id)))?;
+    refreshed.pinned = settings.is_pinned(&id).await;
+    if let Err(e) = settings.save_resolved_names(names.into_fresh(), None).await {
+        warn!("Couldn't save the resolved project name: {}", e);
+    }
+
+    // Swap the refreshed definition into the last scan so it stays current
+    if let Some(existing) = last_scan.projects.lock().await.iter_mut().find(|project| project.id == id) {
+        *existing = refreshed.clone();
+    }
+
+    Ok(Some(refreshed))
+}
+
+#[tauri::command]
+async fn refresh_names(
+    cache: State<'_, ProjectCache>,
+    last_scan: State<'_, LastScan>,
+    settings: State<'_, SettingsState>
+) -> Result<Vec<ProjectDef>, AppError> { // Returns the last scan's projects with their names read again on Ok
+    // Start from nothing so every name gets read out of the metadata again, not just the ones that changed
+    let names = NameCache::new(HashMap::new());
+    let paths: Vec<(String, PathBuf)> = cache.0.lock().await.iter()
+        .map(|(id, path)| (id.clone(), path.clone()))
+        .collect();
+
+    // Reading the metadata means opening files, so keep it off the async runtime
+    let resolved = tauri::async_runtime::spawn_blocking(move || {
+        let resolved: HashMap<String, (metadata::ProjectMetadata, String)> = paths.par_iter()
+            .map(|(id, path)| {
+                let folder_name = path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+                let project_metadata = names.resolve(id, path, &folder_name);
+                (id.clone(), (project_metadata, folder_name))
+            })
+            .collect();
+        (resolved, names.into_fresh())
+    }).await;
+    let (resolved, fresh) = resolved.map_err(|e| AppError::Io(format!("Failed to refresh project names: {}", e)))?;
+
+    // Swap the new names into the last scan, falling back to the folder name like a scan does
+    let mut last_scan_guard = last_scan.projects.lock().await;
+    for project in last_scan_guard.iter_mut() {
+        if let Some((project_metadata, folder_name)) = resolved.get(&project.id) {
+            project.name = project_metadata.display_name.clone().unwrap_or_else(|| folder_name.clone());
+            project.central_guid = project_metadata.central_guid.clone();
+            project.server_hint = project_metadata.server_hint.clone();
+        }
+    }
+
+    settings.save_resolved_names(fresh, None).await?;
+    info!(projects = resolved.len(), "Refreshed project names");
+    Ok(last_scan_guard.clone())
+}
+
+#[tauri::command]
+fn cancel_scan(
+    cancel: State<'_, CancellationToken>
+) {
+    // Flip the flag, get_projects will notice it and return what it has gathered so far
+    cancel.0.store(true, Ordering::Relaxed);
+}
+
+#[tauri::command]
+async fn open_project(
+    id: String,
+    cache: State<'_, ProjectCache>,
+    app: AppHandle
+) -> Result<(), AppError> { // Does not return an Ok, only returns an Err if there is an issue
+    // Try and pull the path from the id on the cache
+    if let Some(path) = cache.0.lock().await.get(&id) {
+        // Open the path in the default explorer window
+        open_in_explorer(&app, path)
+    } else {
+        // If we didn't get anything looking for the id in the cache, log an error.
+        Err(AppError::not_in_cache(&id))
+    }
+}
+
+#[tauri::command]
+async fn check_permissions(
+    id: Option<String>, // The project folder to check, leave out to check the base path
+    cache: State<'_, ProjectCache>,
+    settings: State<'_, SettingsState>
+) -> Result<PermissionCheck, AppError> { // Returns whether we can delete there on Ok, or an Err if there's no folder to check
+    let folder = match id {
+        Some(id) => cache.0.lock().await.get(&id).cloned().ok_or_else(|| AppError::not_in_cache(&id))?,
+        None => settings.snapshot().await.base()?.to_path_buf(),
+    };
+
+    // Making and removing a file is the same as what a delete needs, so if it works here a delete should too
+    let marker = folder.join(format!("{}{}", PERMISSION_MARKER_PREFIX, Uuid::new_v4()));
+    let created = fs::write(&marker, b"");
+    let removed = fs::remove_file(&marker);
+
+    // Whatever happened above, don't leave the marker lying around
+    if marker.exists() {
+        let _ = fs::remove_file(&marker);
+    }
+
+    let error = created.and(removed).err().map(|e| AppError::io(&folder, e));
+    if let Some(e) = &error {
+        warn!("Can't delete in {:?}: {}", folder, e);
+    }
+
+    Ok(PermissionCheck {
+        path: dunce::simplified(&folder).to_string_lossy().into_owned(),
+        can_delete: error.is_none(),
+        error,
+    })
+}
+
+#[tauri::command]
+async fn open_projects(
+    ids: Vec<String>,
+    cache: State<'_, ProjectCache>,
+    app: AppHandle
+) -> Result<OpenProjectsResult, ()> { // Always returns an Ok, each id that fails gets its error in the result instead
+    let warning = (ids.len() > MAX_OPEN_AT_ONCE).then(|| {
+        format!("Only the first {} of {} projects were opened.", MAX_OPEN_AT_ONCE, ids.len())
+    });
+
+    let mut opened = Vec::new();
+    let mut errors = HashMap::new();
+    for id in ids.into_iter().take(MAX_OPEN_AT_ONCE) {
+        // Try and pull the path from the id on the cache, a bad id shouldn't stop the rest opening
+        let path = cache.0.lock().await.get(&id).cloned();
+        let result = match path {
+            Some(path) => open_in_explorer(&app, &path),
+            None => Err(AppError::not_in_cache(&id)),
+        };
+
+        match result {
+            Ok(()) => opened.push(id),
+            Err(e) => {
+                errors.insert(id, e);
+            }
+        }
+    }
+
+    Ok(OpenProjectsResult { opened, errors, warning })
+}
+
+#[tauri::command]
+async fn open_model_url(
+    id: String,
+    cache: State<'_, ProjectCache>,
+    app: AppHandle
+) -> Result<(), AppError> { // Does not return an Ok, only returns an Err if there is an issue
+    // Try and pull the path from the id on the cache
+    let path = cache.0.lock().await.get(&id).cloned()
+        .ok_or_else(|| AppError::not_in_cache(&id))?;
+
+    // The address isn't kept on the project, so read it out of the cache metadata again
+    let folder_name = path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+    let model_url = metadata::resolve(&path, &folder_name).model_url
+        .ok_or_else(|| AppError::PathMissing(format!("Couldn't find a model address for project with ID '{}'.", id)))?;
+
+    // Revit Server addresses only mean something to Revit, there's nothing a browser can do with them
+    if !model_url.starts_with("https://") && !model_url.starts_with("http://") {
+        return Err(AppError::Invalid(format!("{} isn't a web address, it can only be opened from Revit.", model_url)));
+    }
+
+    info!("Opening model address {}", model_url);
+    app.opener()
+        .open_url(model_url, None::<&str>)
+        .map_err(AppError::from)
+}
+
+#[tauri::command]
+async fn copy_path(
+    id: String,
+    cache: State<'_, ProjectCache>,
+    app: AppHandle
+) -> Result<(), AppError> { // Does not return an Ok, only returns an Err if there is an issue
+    // Try and pull the path from the id on the cache
+    let path = cache.0.lock().await.get(&id).cloned()
+        .ok_or_else(|| AppError::not_in_cache(&id))?;
+
+    // Copy it the way Explorer shows it, without any \\?\ prefix
+    app.clipboard()
+        .write_text(dunce::simplified(&path).to_string_lossy().into_owned())
+        .map_err(|e| AppError::Io(format!("Failed to copy {:?} to the clipboard: {}", path, e)))
+}
+
+#[tauri::command]
+async fn get_thumbnail(
+    id: String,
+    cache: State<'_, ProjectCache>
+) -> Result<Option<String>, AppError> { // Returns the preview as a data: URL the frontend can show straight away on Ok, or None if there isn't one
+    // Try and pull the path from the id on the cache
+    let path = cache.0.lock().await.get(&id).cloned()
+        .ok_or_else(|| AppError::not_in_cache(&id))?;
+
+    Ok(metadata::find_thumbnail(&long_path(&path)).map(|(mime, bytes)| {
+        format!("data:{};base64,{}", mime, base64::engine::general_purpose::STANDARD.encode(bytes))
+    }))
+}
+
+#[tauri::command]
+async fn get_project_breakdown(
+    id: String,
+    cache: State<'_, ProjectCache>
+) -> Result<Vec<ExtensionBreakdown>, AppError> { // Returns the extensions biggest first on Ok
+    // Try and pull the path from the id on the cache
+    let path = cache.0.lock().await.get(&id).cloned()
+        .ok_or_else(|| AppError::not_in_cache(&id))?;
+
+    // Add up the count and size of every file, bucketed by its extension
+    let mut by_extension: HashMap<String, (u64, u64)> = HashMap::new();
+    for entry in WalkDir::new(long_path(&path)).into_iter().flatten() {
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
+        if !metadata.is_file() {
+            continue;
+        }
+
+        let extension = entry.path().extension()
+            .map(|ext| ext.to_string_lossy().to_lowercase())
+            .unwrap_or_else(|| "(none)".to_string());
+        let (count, total_bytes) = by_extension.entry(extension).or_default();
+        *count += 1;
+        *total_bytes += metadata.len();
+    }
+
+    let mut breakdown: Vec<ExtensionBreakdown> = by_extension
+        .into_iter()
+        .map(|(extension, (count, total_bytes))| ExtensionBreakdown { extension, count, total_bytes })
+        .collect();
+    breakdown.sort_by_key(|ext| Reverse(ext.total_bytes));
+
+    Ok(breakdown)
+}
+
+#[tauri::command]
+async fn open_largest_file(
+    id: String,
+    cache: State<'_, ProjectCache>,
+    app: AppHandle
+) -> Result<(), AppError> { // Does not return an Ok, only returns an Err if there is an issue
+    // Try and pull the path from the id on the cache
+    let path = cache.0.lock().await.get(&id).cloned()
+        .ok_or_else(|| AppError::not_in_cache(&id))?;
+
+    // Find the single biggest file anywhere under the project
+    let largest = WalkDir::new(long_path(&path))
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| {
+            let metadata = entry.metadata().ok().filter(|metadata| metadata.is_file())?;
+            Some((metadata.len(), entry.into_path()))
+        })
+        .max_by_key(|(size, _)| *size)
+        .map(|(_, largest)| largest)
+        .ok_or_else(|| AppError::Invalid(format!("{:?} doesn't have any files in it.", path)))?;
+
+    // Select it in Explorer, without the \\?\ prefix the walk put on since Explorer doesn't understand it
+    reveal_in_explorer(&app, &largest)
+}
+
+// The opener only takes a string, so refuse paths that aren't valid unicode rather than opening a mangled version of them
+fn opener_path(path: &Path) -> Result<&str, AppError> {
+    path.to_str().ok_or_else(|| AppError::Invalid(format!("{:?} can't be opened, its name isn't valid unicode.", path)))
+}
+
+// Opens a folder (or file) with the default handler, falling back to starting Explorer ourselves if the opener fails.
+// Some machines have the folder handler broken or taken over by something else, Explorer itself still works there.
+fn open_in_explorer(app: &AppHandle, path: &Path) -> Result<(), AppError> {
+    let opened = app.opener().open_path(opener_path(path)?, None::<&str>);
+    explorer_fallback(opened, path, &[path.as_os_str()])
+}
+
+// Same as open_in_explorer, but selects the item in its folder
+fn reveal_in_explorer(app: &AppHandle, path: &Path) -> Result<(), AppError> {
+    let path = dunce::simplified(path);
+    let revealed = app.opener().reveal_item_in_dir(path);
+    let mut select = std::ffi::OsString::from("/select,");
+    select.push(path);
+    explorer_fallback(revealed, path, &[select.as_os_str()])
+}
+
+// Tries Explorer directly when the opener couldn't do it, handing back the opener's error if that doesn't work either
+fn explorer_fallback(result: Result<(), tauri_plugin_opener::Error>, path: &Path, explorer_args: &[&std::ffi::OsStr]) -> Result<(), AppError> {
+    let Err(opener_error) = result else {
+        debug!("Opened {:?} with the opener", path);
+        return Ok(());
+    };
+    warn!("Opener failed for {:?}, trying Explorer: {}", path, opener_error);
+
+    // Explorer's exit code means nothing (it's 1 even when it worked), so starting it is as much as we can check
+    if cfg!(windows) {
+        match std::process::Command::new("explorer.exe").args(explorer_args).spawn() {
+            Ok(_) => {
+                info!("Opened {:?} with explorer.exe", path);
+                return Ok(());
+            }
+            Err(e) => error!("explorer.exe failed for {:?}: {}", path, e),
+        }
+    }
+
+    Err(AppError::from(opener_error))
+}
+
+// Checks that a path sits inside the Revit base path or one of the extra roots, after resolving any .. or links
+fn is_within_roots(path: &Path, revit_cc_base: Option<&Path>, extra_roots: &[PathBuf]) -> bool {
+    let Ok(path) = dunce::canonicalize(path) else {
+        return false;
+    };
+
+    revit_cc_base.into_iter()
+        .chain(extra_roots.iter().map(PathBuf::as_path))
+        .filter_map(|root| dunce::canonicalize(root).ok())
+        .any(|root| path.starts_with(root))
+}
+
+#[tauri::command]
+async fn open_project_parent(
+    id: String,
+    cache: State<'_, ProjectCache>,
+    settings: State<'_, SettingsState>,
+    app: AppHandle
+) -> Result<(), AppError> { // Does not return an Ok, only returns an Err if there is an issue
+    // Try and pull the path from the id on the cache
+    let path = cache.0.lock().await.get(&id).cloned()
+        .ok_or_else(|| AppError::not_in_cache(&id))?;
+
+    // The parent is the user folder the project sits in
+    let parent = path.parent()
+        .filter(|parent| parent.is_dir())
+        .ok_or_else(|| AppError::PathMissing(format!("The folder containing {:?} no longer exists.", path)))?;
+
+    // Make sure we're not about to open something outside the cache if the paths have changed since the scan
+    let roots = settings.snapshot().await;
+    if !is_within_roots(parent, roots.revit_cc_base.as_deref(), &roots.extra_roots) {
+        return Err(AppError::Invalid(format!("{:?} is not inside the Revit cache.", parent)));
+    }
+
+    // Open the path in the default explorer window
+    open_in_explorer(&app, parent)
+}
+
+#[tauri::command]
+async fn open_vers(
+    vers: u16, // We expect a number like "2025", this should be a "year" from ProjectDef
+    settings: State<'_, SettingsState>,
+    app: AppHandle
+) -> Result<Option<String>, AppError> { // Returns a note on Ok if the version folder had to be opened instead of its cache
+    // Only open versions inside the configured range
+    let roots = settings.snapshot().await;
+    if !roots.version_range.contains(vers) {
+        return Err(AppError::Invalid(format!("Revit {} is outside the configured version range.", vers)));
+    }
+
+    // Create path: %localappdata%/Autodesk/Revit/Autodesk Revit {vers}/CollaborationCache
+    let vers_path = version_cache_path(roots.base()?, vers);
+    if vers_path.is_dir() {
+        // Open the path in the default explorer window
+        return open_in_explorer(&app, &vers_path).map(|_| None);
+    }
+
+    // A version that's never opened a central model has no cache folder yet, the folder above it is the next best thing
+    match vers_path.parent().filter(|version_root| version_root.is_dir()) {
+        Some(version_root) => {
+            open_in_explorer(&app, version_root)?;
+            Ok(Some(format!("Revit {} doesn't have a CollaborationCache folder yet, opened its version folder instead.", vers)))
+        }
+        None => Err(AppError::PathMissing(format!("There's no cache for Revit {}.", vers))),
+    }
+}
+
+// Checks whether another process (i.e. Revit) has a file open by asking Windows for exclusive access to it
+#[cfg(windows)]
+fn is_file_locked(path: &Path) -> bool {
+    use std::os::windows::fs::OpenOptionsExt;
+
+    // ERROR_SHARING_VIOLATION and ERROR_LOCK_VIOLATION, anything else (like a read-only file) isn't a lock
+    const SHARING_VIOLATION: i32 = 32;
+    const LOCK_VIOLATION: i32 = 33;
+
+    match fs::OpenOptions::new().read(true).share_mode(0).open(path) {
+        Ok(_) => false,
+        Err(e) => matches!(e.raw_os_error(), Some(SHARING_VIOLATION) | Some(LOCK_VIOLATION)),
+    }
+}
+
+// Other platforms don't hold mandatory locks the way Windows does, so nothing there will block a delete
+#[cfg(not(windows))]
+fn is_file_locked(_path: &Path) -> bool {
+    false
+}
+
+// Checks whether something has a file open for writing, by opening it while refusing to share write access. Cheaper and
+// gentler than is_file_locked, since readers like a virus scanner don't count.
+#[cfg(windows)]
+fn is_file_open_for_write(path: &Path) -> bool {
+    use std::os::windows::fs::OpenOptionsExt;
+
+    const FILE_SHARE_READ: u32 = 0x1;
+    const SHARING_VIOLATION: i32 = 32;
+
+    match fs::OpenOptions::new().read(true).share_mode(FILE_SHARE_READ).open(path) {
+        Ok(_) => false,
+        Err(e) => e.raw_os_error() == Some(SHARING_VIOLATION),
+    }
+}
+
+#[cfg(not(windows))]
+fn is_file_open_for_write(_path: &Path) -> bool {
+    false
+}
+
+// Best guess at whether Revit has the model open right now, going by the model files at the top of the project folder.
+// Anything we can't read just counts as not in use, the delete still checks properly before it starts.
+fn project_in_use(project_path: &Path) -> bool {
+    let Ok(entries) = fs::read_dir(long_path(project_path)) else {
+        return false;
+    };
+
+    entries.flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("rvt")))
+        .any(|path| is_file_open_for_write(&path))
+}
+
+// Walks a folder and lists every file that's currently held open by something else
+fn find_locked_files(path: &Path) -> Vec<PathBuf> {
+    WalkDir::new(long_path(path))
+        .into_iter()
+        .flatten()
+        .filter(|entry| entry.file_type().is_file() && is_file_locked(entry.path()))
+        .map(|entry| dunce::simplified(entry.path()).to_path_buf())
+        .collect()
+}
+
+// How many locked files to list in the error before cutting it short
+const MAX_LOCKED_FILES_REPORTED: usize = 5;
+
+// Makes sure a folder is safe to delete, whichever way it's about to be deleted
+fn check_deletable(path_to_delete: &Path, roots: &ScanRoots) -> Result<(), AppError> {
+    // Never delete anything that isn't inside one of the cache roots, whatever the cache says the id maps to. The root
+    // itself doesn't count as inside, that would take every project with it.
+    let canonical_path = dunce::canonicalize(path_to_delete).ok();
+    let is_root = roots.revit_cc_base.iter()
+        .chain(roots.extra_roots.iter())
+        .filter_map(|root| dunce::canonicalize(root).ok())
+        .any(|root| canonical_path.as_ref() == Some(&root));
+    if is_root || !is_within_roots(path_to_delete, roots.revit_cc_base.as_deref(), &roots.extra_roots) {
+        let error_message = format!("Refusing to delete {:?}, it is not inside the Revit cache.", path_to_delete);
+        error!("{}", error_message);
+        return Err(AppError::Invalid(error_message));
+    }
+
+    // Don't start deleting if Revit is holding any of the files, otherwise we'd leave a half-deleted folder behind
+    let locked_files = find_locked_files(path_to_delete);
+    if !locked_files.is_empty() {
+        let listed: Vec<String> = locked_files.iter()
+            .take(MAX_LOCKED_FILES_REPORTED)
+            .map(|path| path.to_string_lossy().into_owned())
+            .collect();
+        let error_message = format!(
+            "{} file(s) in {:?} are in use, close Revit first: {}",
+            locked_files.len(), path_to_delete, listed.join(", ")
+        );
+        warn!("{}", error_message);
+        return Err(AppError::InUse(error_message));
+    }
+
+    Ok(())
+}
+
+// Deletes every file under a folder one by one so progress can be reported as it goes, then takes out whatever's left.
+// The counts carry over between calls, so a retry after a failure picks up where the last go stopped.
+fn remove_dir_with_progress(path: &Path, removed: &mut (u64, u64), on_progress: &dyn Fn(u64, u64)) -> Result<(), AppError> {
+    for entry in WalkDir::new(long_path(path)).into_iter().flatten() {
+        if !entry.file_type().is_file() {
+            continue;
+        }
+
+        let size = entry.metadata().map(|metadata| metadata.len()).unwrap_or(0);
+        let mut result = fs::remove_file(entry.path());
+
+        // Revit marks some cache files read-only, which stops them being removed on Windows
+        if result.as_ref().is_err_and(|e| e.kind() == std::io::ErrorKind::PermissionDenied) {
+            if let Ok(metadata) = entry.metadata() {
+                let mut permissions = metadata.permissions();
+                #[allow(clippy::permissions_set_readonly_false)]
+                permissions.set_readonly(false);
+                let _ = fs::set_permissions(entry.path(), permissions);
+            }
+            result = fs::remove_file(entry.path());
+        }
+        result.map_err(|e| AppError::io(entry.path(), e))?;
+
+        removed.0 += 1;
+        removed.1 += size;
+        if removed.0.is_multiple_of(DELETE_PROGRESS_EVERY) {
+            on_progress(removed.0, removed.1);
+        }
+    }
+
+    // Only the empty folders (and any links, which we never follow) are left by now
+    fs::remove_dir_all(long_path(path)).map_err(|e| AppError::io(path, e))?;
+    on_progress(removed.0, removed.1);
+    Ok(())
+}
+
+// Removes a project folder, sending it to the recycle bin unless a permanent delete was asked for. on_progress is given
+// the files removed and bytes freed so far, the recycle bin is a single call so it only gets reported for permanent deletes.
+#[tracing::instrument(skip(roots, on_progress))]
+fn remove_project_folder(path_to_delete: &Path, permanent: bool, roots: &ScanRoots, on_progress: &dyn Fn(u64, u64)) -> Result<(), AppError> {
+    check_deletable(path_to_delete, roots)?;
+
+    // Either destroy the folder outright, or hand it to the OS recycle bin so it can be restored from Explorer
+    let mut removed = (0, 0);
+    let mut remove = || if permanent {
+        remove_dir_with_progress(path_to_delete, &mut removed, on_progress)
+    } else {
+        trash::delete(path_to_delete)
+            .map_err(|e| AppError::Io(format!("Failed to send {:?} to the recycle bin: {}", path_to_delete, e)))
+    };
+
+    // Windows can hang on to handles for a moment after Revit closes, so give it a few more goes before giving up
+    let mut result = remove();
+    for (attempt, delay) in DELETE_RETRY_DELAYS.iter().enumerate() {
+        let Err(e) = &result else {
+            break;
+        };
+        warn!(attempt = attempt + 1, delay_ms = delay.as_millis() as u64, "Delete failed, retrying: {}", e);
+        std::thread::sleep(*delay);
+        result = remove();
+    }
+
+    match result {
+        Ok(_) => { // if the removal returns an Ok
+            info!("Successfully Deleted Directory");
+            Ok(())
+        },
+        Err(e) => { // if the removal returns an error, we should pass it along
+            error!("Failed to delete directory {:?}: {}", path_to_delete, e);
+            Err(e)
+        }
+    }
+}
+
+// Moves a project folder into the quarantine instead of deleting it, returning where it ended up. None means it had to
+// go to the recycle bin instead, so there's nothing in the quarantine to restore.
+#[tracing::instrument(skip(roots, quarantine_dir))]
+fn quarantine_project_folder(id: &str, path_to_delete: &Path, roots: &ScanRoots, quarantine_dir: Option<&Path>) -> Result<Option<PathBuf>, AppError> {
+    check_deletable(path_to_delete, roots)?;
+
+    let quarantine_dir = quarantine_dir
+        .ok_or_else(|| AppError::PathMissing("Couldn't find the app data directory to quarantine into.".to_string()))?;
+    fs::create_dir_all(quarantine_dir).map_err(|e| AppError::io(quarantine_dir, e))?;
+
+    // A rename is instant and keeps everything intact, but it only works on the same drive. A cache root on another
+    // drive can't be quarantined, so it goes to the recycle bin instead and can still be put back from there.
+    let quarantined = quarantine_dir.join(id);
+    match fs::rename(long_path(path_to_delete), long_path(&quarantined)) {
+        Ok(()) => {
+            info!("Moved {:?} into quarantine at {:?}", path_to_delete, quarantined);
+            Ok(Some(quarantined))
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::CrossesDevices => {
+            info!("{:?} is on another drive from the quarantine, sending it to the recycle bin", path_to_delete);
+            remove_project_folder(path_to_delete, false, roots, &|_, _| {}).map(|_| None)
+        }
+        Err(e) => Err(AppError::io(path_to_delete, e)),
+    }
+}
+
+// Refuses to go any further with a project that's been pinned
+async fn check_not_pinned(id: &str, settings: &SettingsState) -> Result<(), AppError> {
+    if settings.is_pinned(id).await {
+        return Err(AppError::Invalid(format!("Project with ID '{}' is pinned, unpin it before deleting.", id)));
+    }
+    Ok(())
+}
+
+// Checks the token against the pending delete, clearing it out if it has expired
+fn check_pending_delete<'a>(pending: &'a mut Option<PendingDeleteToken>, token: &str) -> Result<&'a mut PendingDeleteToken, AppError> {
+    // Drop the pending delete if it's too old to use
+    if pending.as_ref().is_some_and(|pending| pending.created.elapsed() > DELETE_TOKEN_LIFETIME) {
+        *pending = None;
+        return Err(AppError::Invalid("The delete confirmation has expired, please try again.".to_string()));
+    }
+
+    pending.as_mut()
+        .filter(|pending| pending.token == token)
+        .ok_or_else(|| AppError::Invalid("No matching delete has been prepared, please try again.".to_string()))
+}
+
+// Uses up the token, handing back the ids it was prepared with so the same delete can't be submitted twice
+async fn take_pending_ids(pending: &PendingDelete, token: &str) -> Result<Vec<String>, AppError> {
+    let mut pending_guard = pending.0.lock().await;
+    let ids = std::mem::take(&mut check_pending_delete(&mut pending_guard, token)?.ids);
+    *pending_guard = None;
+    Ok(ids)
+}
+
+// The bulk deletes pick their projects out of the last scan, which mustn't be from a previous session or from before
+// something else changed on disk
+async fn check_scan_fresh(last_scan: &LastScan) -> Result<(), AppError> {
+    if last_scan.stamp.lock().await.stale {
+        return Err(AppError::Invalid("The project list is out of date, please scan again first.".to_string()));
+    }
+    Ok(())
+}
+
+#[tauri::command]
+async fn prepare_delete(
+    ids: Vec<String>,
+    cache: State<'_, ProjectCache>,
+    pending: State<'_, PendingDelete>
+) -> Result<DeletePreparation, ()> { // Always returns an Ok, nothing is touched on disk
+    // Measure what would be deleted so the frontend can show it alongside the confirmation
+    let preview = build_delete_preview(&ids, &cache).await;
+
+    // Store the ids against a fresh token, replacing any delete that was prepared before
+    let token = Uuid::new_v4().to_string();
+    *pending.0.lock().await = Some(PendingDeleteToken {
+        token: token.clone(),
+        ids,
+        created: Instant::now(),
+    });
+
+    Ok(DeletePreparation {
+        token,
+        expires_in_secs: DELETE_TOKEN_LIFETIME.as_secs(),
+        preview,
+    })
+}
+
+#[tauri::command]
+async fn delete_folder(
+    id: String,
+    token: String, // From prepare_delete, the id has to be one of the ones it was prepared with
+    permanent: Option<bool>, // Skip the quarantine and delete the folder for good when this is explicitly true
+    quarantine: Option<bool>, // The folder is moved into the quarantine unless this is false (or permanent is set), so restore can undo it
+    pending: State<'_, PendingDelete>,
+    app: AppHandle
+) -> Result<(), AppError> { // Does not return an Ok, only retuns an Err if there is an issue
+    // Make sure this delete was prepared, and take the id out of it so it can't be deleted twice
+    {
+        let mut pending_guard = pending.0.lock().await;
+        let pending_delete = check_pending_delete(&mut pending_guard, &token)?;
+        let count_before = pending_delete.ids.len();
+        pending_delete.ids.retain(|pending_id| pending_id != &id);
+        if pending_delete.ids.len() == count_before {
+            return Err(AppError::Invalid(format!("Project with ID '{}' was not part of the prepared delete.", id)));
+        }
+    }
+
+    // Same as a batch, just with the one id. A single delete can be undone in the app by default, turning the
+    // quarantine off sends it to the recycle bin the same as a batch.
+    let quarantine = quarantine.unwrap_or(!permanent.unwrap_or(false));
+    match delete_ids(vec![id.clone()], DeleteMode::from_flags(permanent, Some(quarantine)), &app).await.remove(&id) {
+        Some(DeleteOutcome::Success) => {
+            emit_disk_space(&app);
+            Ok(())
+        }
+        // Nothing changed on disk, so there's no new disk space to send
+        Some(DeleteOutcome::DryRun { .. }) => Ok(()),
+        Some(DeleteOutcome::Error(e)) => Err(e),
+        // Every id gets an outcome, so this would be a bug rather than anything the user did
+        None => Err(AppError::Io(format!("The delete of project with ID '{}' didn't report back.", id))),
+    }
+}
+
+#[tauri::command]
+async fn delete_folders(
+    token: String, // From prepare_delete, the ids it was prepared with are the ones that get deleted
+    permanent: Option<bool>, // Folders go to the recycle bin unless this is explicitly true
+    quarantine: Option<bool>, // Move the folders into the quarantine instead, so restore can undo them
+    pending: State<'_, PendingDelete>,
+    app: AppHandle
+) -> Result<HashMap<String, DeleteOutcome>, AppError> { // Returns each id's outcome in the map on Ok, or an Err if the token isn't valid
+    // Use up the token so the same delete can't be submitted twice
+    let ids = take_pending_ids(&pending, &token).await?;
+
+    let outcomes = delete_ids(ids, DeleteMode::from_flags(permanent, quarantine), &app).await;
+    if outcomes.values().any(|outcome| matches!(outcome, DeleteOutcome::Success)) {
+        emit_disk_space(&app);
+    }
+    Ok(outcomes)
+}
+
+// How delete_ids gets rid of each folder
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum DeleteMode {
+    RecycleBin,
+    Permanent,
+    Quarantine, // Moved aside so it can be restored, wins over a permanent delete if both are asked for
+}
+
+impl DeleteMode {
+    fn from_flags(permanent: Option<bool>, quarantine: Option<bool>) -> Self {
+        if quarantine.unwrap_or(false) {
+            DeleteMode::Quarantine
+        } else if permanent.unwrap_or(false) {
+            DeleteMode::Permanent
+        } else {
+            DeleteMode::RecycleBin
+        }
+    }
+}
+
+// What happened to a folder that delete_ids got rid of, or would have in a dry run
+enum Removed {
+    Gone(Option<PathBuf>), // Where it was quarantined to, None if it was deleted or sent to the recycle bin
+    DryRun(u64), // The bytes it would have freed
+}
+
+// Deletes each id's folder, a few at a time up to the delete concurrency, a failure on one doesn't stop the rest of the batch
+async fn delete_ids(ids: Vec<String>, mode: DeleteMode, app: &AppHandle) -> HashMap<String, DeleteOutcome> {
+    // Pull the state we need off the app handle, the same as scan_projects
+    let cache = app.state::<ProjectCache>();
+    let settings = app.state::<SettingsState>();
+    let last_scan = app.state::<LastScan>();
+    let quarantine = app.state::<Quarantine>();
+
+    // Take the roots once for the whole batch
+    let roots = settings.snapshot().await;
+    let current_settings = settings.get().await;
+    let concurrency = delete_concurrency(current_settings.delete_concurrency, &roots);
+    let dry_run = current_settings.dry_run;
+
+    // A dry run reports the size the last scan found, the same one the list and the delete preview show
+    let known_sizes: HashMap<String, u64> = if dry_run {
+        last_scan.projects.lock().await.iter().map(|project| (project.id.clone(), project.size)).collect()
+    } else {
+        HashMap::new()
+    };
+
+    // Container map for the outcome of each id
+    let mut outcomes = HashMap::new();
+
+    // Claim every path up front, so nothing else can start on the same folders while the batch runs
+    let mut to_delete = Vec::new();
+    for id in ids {
+        // Leave pinned projects alone, they stay in the cache since nothing happened to them
+        if let Err(e) = check_not_pinned(&id, &settings).await {
+            outcomes.insert(id, DeleteOutcome::Error(e));
+            continue;
+        }
+
+        // Try and pull the path from the id on the cache, a failure here shouldn't stop the rest of the batch
+        let Some(path_to_delete) = cache.0.lock().await.remove(&id) else {
+            outcomes.insert(id.clone(), DeleteOutcome::Error(AppError::not_in_cache(&id)));
+            continue;
+        };
+        to_delete.push((id, path_to_delete));
+    }
+
+    // Do the actual deleting on a pool no bigger than the concurrency limit, off the async runtime since it all blocks
+    info!(count = to_delete.len(), concurrency, ?mode, dry_run, "Deleting projects");
+    let quarantine_dir = quarantine.dir.clone();
+    let progress_app = app.clone();
+    let total = to_delete.len();
+    let completed = AtomicUsize::new(0);
+    let claimed = to_delete.clone(); // Kept back in case the batch dies, so every folder can still be accounted for
+    let delete_one = move |(id, path_to_delete): (String, PathBuf)| {
+        // Quarantining hands back where the folder went, the other modes leave nothing behind
+        let result = match mode {
+            // The same checks as a real delete so the outcome is the same, just without the delete at the end. Anything
+            // the last scan didn't size gets walked for it instead.
+            _ if dry_run => check_deletable(&path_to_delete, &roots).map(|_| {
+                let bytes_freed = known_sizes.get(&id).copied().unwrap_or_else(|| folder_size(&path_to_delete));
+                info!(bytes_freed, "Dry run, would delete {:?}", path_to_delete);
+                Removed::DryRun(bytes_freed)
+            }),
+            DeleteMode::Quarantine => quarantine_project_folder(&id, &path_to_delete, &roots, quarantine_dir.as_deref()).map(Removed::Gone),
+            _ => {
+                let on_progress = |files_removed, bytes_freed| {
+                    let _ = progress_app.emit("delete-progress", DeleteProgress { id: id.clone(), files_removed, bytes_freed });
+                };
+                remove_project_folder(&path_to_delete, mode == DeleteMode::Permanent, &roots, &on_progress).map(|_| Removed::Gone(None))
+            }
+        };
+
+        let completed = completed.fetch_add(1, Ordering::Relaxed) + 1;
+        let _ = progress_app.emit("delete-batch-progress", DeleteBatchProgress { id: id.clone(), completed, total });
+        (id, path_to_delete, result)
+    };
+    let results = tauri::async_runtime::spawn_blocking(move || {
+        match rayon::ThreadPoolBuilder::new().num_threads(concurrency).build() {
+            Ok(pool) => pool.install(|| to_delete.into_par_iter().map(delete_one).collect()),
+            Err(e) => {
+                // Not worth failing the batch over, one at a time still gets there
+                warn!("Couldn't start the delete pool, deleting one at a time: {}", e);
+                to_delete.into_iter().map(delete_one).collect()
+            }
+        }
+    }).await;
+    let results: Vec<_> = match results {
+        Ok(results) => results,
+        Err(e) => {
+            // There's no telling which folders went before it stopped, so they all fail and go back in the cache. Some
+            // may be gone from disk, so the last scan can't be trusted either.
+            error!("Delete batch stopped unexpectedly: {}", e);
+            last_scan.mark_stale().await;
+            claimed.into_iter()
+                .map(|(id, path_to_delete)| {
+                    let error = AppError::Io(format!("The delete stopped unexpectedly before finishing: {}", e));
+                    (id, path_to_delete, Err(error))
+                })
+                .collect()
+        }
+    };
+
+    for (id, path_to_delete, result) in results {
+        let outcome = match result {
+            // Nothing actually went, so the folder goes back in the cache the same as after a failure
+            Ok(Removed::DryRun(bytes_freed)) => {
+                cache.0.lock().await.insert(id.clone(), path_to_delete);
+                DeleteOutcome::DryRun { bytes_freed }
+            }
+            Ok(Removed::Gone(quarantined)) => {
+                // The project is gone, so it comes out of the last scan too and the list can be drawn from it straight away
+                let project = {
+                    let mut projects = last_scan.projects.lock().await;
+                    let index = projects.iter().position(|project| project.id == id);
+                    index.map(|index| projects.remove(index))
+                };
+
+                // A quarantined project keeps its row, so it can come back with it on a restore
+                if let Some(quarantined) = quarantined {
+                    quarantine.entries.lock().await.push(QuarantinedProject {
+                        id: id.clone(),
+                        original: path_to_delete,
+                        quarantined,
+                        project,
+                    });
+                }
+                last_scan.mark_stale().await;
+                DeleteOutcome::Success
+            }
+            Err(e) => {
+                // The folder is still there, so keep it in the cache for another go
+                cache.0.lock().await.insert(id.clone(), path_to_delete);
+                DeleteOutcome::Error(e)
+            }
+        };
+
+        outcomes.insert(id, outcome);
+    }
+
+    outcomes
+}
+
+// How many folders a batch delete works on at once. An explicit setting wins, otherwise it goes by the drive the cache
+// is on: a spinning disk only gets slower with more than one at a time, an SSD copes fine with a few.
+fn delete_concurrency(setting: Option<usize>, roots: &ScanRoots) -> usize {
+    if let Some(concurrency) = setting {
+        return concurrency;
+    }
+
+    let Some(path) = roots.revit_cc_base.as_deref().and_then(|path| dunce::canonicalize(path).ok()) else {
+        return 1;
+    };
+    let disks = sysinfo::Disks::new_with_refreshed_list();
+    match find_disk(&disks, &path).map(|disk| disk.kind()) {
+        Some(sysinfo::DiskKind::SSD) => SSD_DELETE_CONCURRENCY,
+        _ => 1,
+    }
+}
+
+#[tauri::command]
+async fn set_delete_concurrency(
+    concurrency: Option<usize>, // None goes back to picking based on the drive
+    settings: State<'_, SettingsState>
+) -> Result<usize, AppError> { // Returns the concurrency deletes will now use on Ok, or an Err if it's out of range
+    // Saved so it survives a restart, the range is checked along with everything else
+    settings.update(|settings| {
+        settings.delete_concurrency = concurrency;
+        Ok(())
+    }).await?;
+
+    Ok(delete_concurrency(concurrency, &settings.snapshot().await))
+}
+
+// Moves a quarantined project back where it came from and puts it back in the cache and the list
+async fn restore_quarantined(entry: &QuarantinedProject, app: &AppHandle) -> Result<Option<ProjectDef>, AppError> {
+    // Don't clobber anything Revit has put back in the meantime, the quarantined copy stays put so it can be purged
+    if entry.original.exists() {
+        return Err(AppError::Invalid(format!("Can't restore {:?}, something is already there.", entry.original)));
+    }
+
+    fs::rename(long_path(&entry.quarantined), long_path(&entry.original))
+        .map_err(|e| AppError::io(&entry.quarantined, e))?;
+    info!("Restored {:?} from quarantine", entry.original);
+
+    app.state::<ProjectCache>().0.lock().await.insert(entry.id.clone(), entry.original.clone());
+    let last_scan = app.state::<LastScan>();
+    if let Some(project) = &entry.project {
+        last_scan.projects.lock().await.push(project.clone());
+    }
+    last_scan.mark_stale().await;
+
+    Ok(entry.project.clone())
+}
+
+#[tauri::command]
+async fn restore(
+    id: String,
+    quarantine: State<'_, Quarantine>,
+    app: AppHandle
+) -> Result<Option<ProjectDef>, AppError> { // Returns the restored project on Ok if it was in the last scan, or an Err if it couldn't be moved back
+    let mut entries = quarantine.entries.lock().await;
+    let index = entries.iter().position(|entry| entry.id == id)
+        .ok_or_else(|| AppError::Invalid(format!("Project with ID '{}' is not in the quarantine.", id)))?;
+
+    // Only take it off the quarantine once it's actually back, so a failed restore can be tried again
+    let project = restore_quarantined(&entries[index], &app).await?;
+    entries.remove(index);
+    Ok(project)
+}
+
+#[tauri::command]
+async fn restore_last(
+    quarantine: State<'_, Quarantine>,
+    app: AppHandle
+) -> Result<Option<ProjectDef>, AppError> { // Returns the restored project on Ok, None if there was nothing to undo, or an Err if it couldn't be moved back
+    let mut entries = quarantine.entries.lock().await;
+    let Some(entry) = entries.last() else {
+        return Ok(None);
+    };
+
+    let project = restore_quarantined(entry, &app).await?;
+    entries.pop();
+    Ok(project)
+}
+
+#[tauri::command]
+async fn purge_quarantine(
+    quarantine: State<'_, Quarantine>,
+    settings: State<'_, SettingsState>
+) -> Result<u64, AppError> { // Returns the bytes freed (or that would be in a dry run) on Ok, or an Err if the quarantine couldn't be removed
+    let mut entries = quarantine.entries.lock().await;
+    let Some(dir) = quarantine.dir.as_deref().filter(|dir| dir.exists()) else {
+        entries.clear();
+        return Ok(0);
+    };
+
+    // Sizing and removing everything in there is slow, so keep it off the async runtime. A dry run only sizes it, and
+    // everything stays restorable.
+    let dry_run = settings.dry_run().await;
+    let dir = dir.to_path_buf();
+    let quarantined: Vec<PathBuf> = entries.iter().map(|entry| entry.quarantined.clone()).collect();
+    let freed = tauri::async_runtime::spawn_blocking(move || {
+        let freed = quarantined.iter().map(|path| folder_size(path)).sum();
+        if dry_run {
+            info!("Dry run, would purge {:?}", dir);
+        } else {
+            fs::remove_dir_all(long_path(&dir)).map_err(|e| AppError::io(&dir, e))?;
+        }
+        Ok::<u64, AppError>(freed)
+    }).await.map_err(|e| AppError::Io(format!("Purging the quarantine stopped unexpectedly: {}", e)))??;
+    info!(count = entries.len(), freed, dry_run, "Purged quarantine");
+    if !dry_run {
+        entries.clear();
+    }
+
+    Ok(freed)
+}
+
+#[tauri::command]
+async fn delete_orphaned(
+    token: String, // From prepare_delete, only orphaned projects it was prepared with get deleted
+    permanent: Option<bool>, // Folders go to the recycle bin unless this is explicitly true
+    pending: State<'_, PendingDelete>,
+    last_scan: State<'_, LastScan>,
+    app: AppHandle
+) -> Result<HashMap<String, DeleteOutcome>, AppError> { // Returns each id's outcome in the map on Ok, or an Err if the token or the scan isn't valid
+    check_scan_fresh(&last_scan).await?;
+    let confirmed: HashSet<String> = take_pending_ids(&pending, &token).await?.into_iter().collect();
+
+    // Everything the last scan flagged as orphaned that was confirmed, apart from anything pinned
+    let ids: Vec<String> = last_scan.projects.lock().await
+        .iter()
+        .filter(|project| project.orphaned && !project.pinned && confirmed.contains(&project.id))
+        .map(|project| project.id.clone())
+        .collect();
+
+    info!(count = ids.len(), "Deleting orphaned projects");
+    Ok(delete_ids(ids, DeleteMode::from_flags(permanent, None), &app).await)
+}
+
+#[tauri::command]
+async fn clean_all(
+    token: String, // From prepare_delete, anything it wasn't prepared with is skipped
+    permanent: Option<bool>, // Folders go to the recycle bin unless this is explicitly true
+    pending: State<'_, PendingDelete>,
+    cache: State<'_, ProjectCache>,
+    last_scan: State<'_, LastScan>,
+    settings: State<'_, SettingsState>,
+    app: AppHandle
+) -> Result<CleanAllResult, AppError> { // Returns what was deleted and skipped on Ok, or an Err if the token or the scan isn't valid
+    // Everything goes off the last scan, so it has to be current and the user has to have confirmed what's in it
+    check_scan_fresh(&last_scan).await?;
+    let confirmed: HashSet<String> = take_pending_ids(&pending, &token).await?.into_iter().collect();
+
+    let pinned_ids = settings.pinned().await;
+    let projects = last_scan.projects.lock().await.clone();
+    let paths = cache.0.lock().await.clone();
+
+    // Sort out what's safe to go before touching anything. Whether Revit has a project open is checked again now rather
+    // than trusting the scan, it could have been opened since.
+    let mut skipped = Vec::new();
+    let mut ids = Vec::new();
+    for project in &projects {
+        let Some(path) = paths.get(&project.id) else {
+            continue;
+        };
+
+        let reason = if !confirmed.contains(&project.id) {
+            Some(SkipReason::NotConfirmed)
+        } else if pinned_ids.contains(&project.id) {
+            Some(SkipReason::Pinned)
+        } else if project_in_use(path) {
+            Some(SkipReason::InUse)
+        } else {
+            None
+        };
+        match reason {
+            Some(reason) => skipped.push(SkippedProject { id: project.id.clone(), name: project.name.clone(), reason }),
+            None => ids.push(project.id.clone()),
+        }
+    }
+
+    // delete_ids still checks every path is inside a cache root and not pinned before it goes
+    info!(count = ids.len(), skipped = skipped.len(), "Cleaning all projects");
+    let outcomes = delete_ids(ids, DeleteMode::from_flags(permanent, None), &app).await;
+
+    let mut bytes_freed = 0;
+    let mut deleted_count = 0;
+    for project in projects {
+        match outcomes.get(&project.id) {
+            Some(DeleteOutcome::Success) => {
+                bytes_freed += project.size;
+                deleted_count += 1;
+            }
+            // Counted the same as a real delete, so a dry run shows what the clean would have done
+            Some(DeleteOutcome::DryRun { bytes_freed: would_free }) => {
+                bytes_freed += would_free;
+                deleted_count += 1;
+            }
+            Some(DeleteOutcome::Error(e)) => {
+                skipped.push(SkippedProject { id: project.id, name: project.name, reason: SkipReason::Failed(e.clone()) });
+            }
+            None => {}
+        }
+    }
+
+    info!(bytes_freed, deleted_count, skipped = skipped.len(), "Cleaned all projects");
+    Ok(CleanAllResult { bytes_freed, deleted_count, skipped })
+}
+
+#[tauri::command]
+async fn delete_empty(
+    token: String, // From prepare_delete, only empty projects it was prepared with get deleted
+    permanent: Option<bool>, // Folders go to the recycle bin unless this is explicitly true
+    pending: State<'_, PendingDelete>,
+    last_scan: State<'_, LastScan>,
+    app: AppHandle
+) -> Result<HashMap<String, DeleteOutcome>, AppError> { // Returns each id's outcome in the map on Ok, or an Err if the token or the scan isn't valid
+    check_scan_fresh(&last_scan).await?;
+    let confirmed: HashSet<String> = take_pending_ids(&pending, &token).await?.into_iter().collect();
+
+    // Everything the last scan found nothing in that was confirmed, apart from anything pinned
+    let ids: Vec<String> = last_scan.projects.lock().await
+        .iter()
+        .filter(|project| project.empty && !project.pinned && confirmed.contains(&project.id))
+        .map(|project| project.id.clone())
+        .collect();
+
+    info!(count = ids.len(), "Deleting empty projects");
+    Ok(delete_ids(ids, DeleteMode::from_flags(permanent, None), &app).await)
+}
+
+#[tauri::command]
+async fn delete_version(
+    vers: u16, // The Revit year whose whole CollaborationCache should be cleared
+    permanent: Option<bool>, // Folders go to the recycle bin unless this is explicitly true
+    preview: Option<bool>, // Only add up what would be freed, without deleting anything
+    token: Option<String>, // From prepare_delete, needed unless previewing. Projects it wasn't prepared with are left alone.
+    app: AppHandle
+) -> Result<u64, AppError> { // Returns the bytes freed (or that would be) on Ok
+    // Pull the state we need off the app handle, the same as delete_ids
+    let pending = app.state::<PendingDelete>();
+    let cache = app.state::<ProjectCache>();
+    let last_scan = app.state::<LastScan>();
+    let settings = app.state::<SettingsState>();
+
+    let preview = preview.unwrap_or(false);
+    let confirmed: HashSet<String> = match (&token, preview) {
+        (_, true) => HashSet::new(),
+        (Some(token), false) => take_pending_ids(&pending, token).await?.into_iter().collect(),
+        (None, false) => return Err(AppError::Invalid("Clearing a version has to be prepared first.".to_string())),
+    };
+
+    let roots = settings.snapshot().await;
+    if !roots.version_range.contains(vers) {
+        return Err(AppError::Invalid(format!("Revit {} is outside the configured version range.", vers)));
+    }
+
+    let vers_path = version_cache_path(roots.base()?, vers);
+    if !vers_path.is_dir() {
+        return Err(AppError::PathMissing(format!("Revit {} has no CollaborationCache folder.", vers)));
+    }
+
+    let pinned_ids = settings.pinned().await;
+    let permanent = permanent.unwrap_or(false);
+    let dry_run = settings.dry_run().await;
+
+    // Sizing and removing every project is slow and the removes wait between retries, so keep it off the async runtime
+    let (bytes_freed, deleted_ids) = tauri::async_runtime::spawn_blocking(move || {
+        // Go through each project rather than the whole folder, so pinned projects and locked files still get the usual checks
+        let mut project_folders = Vec::new();
+        find_root_project_folders(&vers_path, vers, &mut Vec::new(), &mut project_folders);
+
+        let mut bytes_freed = 0;
+        let mut deleted_ids = HashSet::new();
+        for project_folder in project_folders {
+            let id = project_id(&project_folder.path);
+            if pinned_ids.contains(&id) {
+                info!(id = %id, "Skipping pinned project");
+                continue;
+            }
+            if !preview && !confirmed.contains(&id) {
+                info!(id = %id, "Skipping project that wasn't part of the prepared delete");
+                continue;
+            }
+
+            let size = folder_size(&project_folder.path);
+            if preview {
+                bytes_freed += size;
+                continue;
+            }
+            if dry_run {
+                if check_deletable(&project_folder.path, &roots).is_ok() {
+                    info!("Dry run, would delete {:?}", project_folder.path);
+                    bytes_freed += size;
+                }
+                continue;
+            }
+
+            // Failures are already logged, the rest of the version still gets cleared
+            if remove_project_folder(&project_folder.path, permanent, &roots, &|_, _| {}).is_ok() {
+                bytes_freed += size;
+                deleted_ids.insert(id);
+            }
+        }
+        (bytes_freed, deleted_ids)
+    }).await.map_err(|e| AppError::Io(format!("Clearing Revit {} stopped unexpectedly: {}", vers, e)))?;
+
+    // Forget about everything that was removed
+    cache.0.lock().await.retain(|id, _| !deleted_ids.contains(id));
+    last_scan.projects.lock().await.retain(|project| !deleted_ids.contains(&project.id));
+    if !deleted_ids.is_empty() {
+        last_scan.mark_stale().await;
+    }
+
+    info!(version = vers, bytes_freed, preview, dry_run, "Cleared Revit version cache");
+    Ok(bytes_freed)
+}
+
+#[tauri::command]
+async fn purge_old_revisions(
+    id: String,
+    permanent: Option<bool>, // Folders go to the recycle bin unless this is explicitly true
+    cache: State<'_, ProjectCache>,
+    last_scan: State<'_, LastScan>,
+    settings: State<'_, SettingsState>
+) -> Result<u64, AppError> { // Returns the bytes freed on Ok
+    check_not_pinned(&id, &settings).await?;
+
+    // Try and pull the path from the id on the cache
+    let path = cache.0.lock().await.get(&id).cloned()
+        .ok_or_else(|| AppError::not_in_cache(&id))?;
+
+    let roots = settings.snapshot().await;
+    let permanent = permanent.unwrap_or(false);
+    let dry_run = settings.dry_run().await;
+
+    // Walking every revision for its dates and removing them is slow, so keep it off the async runtime. Whatever got
+    // removed before a failure is counted either way, so the last scan still gets marked out of date.
+    let (removed, bytes_freed, result) = tauri::async_runtime::spawn_blocking(move || {
+        // Keep the latest revision, the rest go. Anything that isn't a revision folder stays put.
+        let revisions = match old_revisions(&path) {
+            Ok(revisions) => revisions,
+            Err(e) => return (0, 0, Err(AppError::io(&path, e))),
+        };
+
+        let mut removed = 0;
+        let mut bytes_freed = 0;
+        for revision in revisions {
+            let size = folder_size(&revision);
+            if dry_run {
+                if let Err(e) = check_deletable(&revision, &roots) {
+                    return (removed, bytes_freed, Err(e));
+                }
+                info!("Dry run, would delete {:?}", revision);
+                bytes_freed += size;
+                continue;
+            }
+            if let Err(e) = remove_project_folder(&revision, permanent, &roots, &|_, _| {}) {
+                return (removed, bytes_freed, Err(e));
+            }
+            removed += 1;
+            bytes_freed += size;
+        }
+        (removed, bytes_freed, Ok(()))
+    }).await.map_err(|e| AppError::Io(format!("Purging old revisions stopped unexpectedly: {}", e)))?;
+
+    if removed > 0 {
+        last_scan.mark_stale().await;
+    }
+    result?;
+
+    info!(id = %id, bytes_freed, "Purged old revisions");
+    Ok(bytes_freed)
+}
+
+#[tauri::command]
+async fn trim_project(
+    id: String,
+    older_than_days: u64, // Files last modified more than this many days ago are removed, the rest stay
+    permanent: Option<bool>, // Files go to the recycle bin unless this is explicitly true
+    force: Option<bool>, // Go ahead even if every file is old enough, which leaves the project empty
+    cache: State<'_, ProjectCache>,
+    last_scan: State<'_, LastScan>,
+    settings: State<'_, SettingsState>
+) -> Result<TrimResult, AppError> { // Returns what was removed on Ok, or an Err if the trim would empty the project without force
+    check_not_pinned(&id, &settings).await?;
+
+    // Try and pull the path from the id on the cache
+    let path = cache.0.lock().await.get(&id).cloned()
+        .ok_or_else(|| AppError::not_in_cache(&id))?;
+
+    let roots = settings.snapshot().await;
+    let dry_run = settings.dry_run().await;
+    let permanent = permanent.unwrap_or(false);
+    let force = force.unwrap_or(false);
+
+    // Checking for locked files, walking the project and removing files one by one are all slow, so keep them off the
+    // async runtime. It also hands back whether anything on disk might have changed, even if the trim then failed.
+    let trim_id = id.clone();
+    let (trimmed, touched_disk) = tauri::async_runtime::spawn_blocking(move || {
+        let mut touched_disk = false;
+        let mut trim = || -> Result<TrimResult, AppError> {
+            check_deletable(&path, &roots)?;
+
+            // Pick out the old files, keeping count of everything so we know if they're all going
+            let mut total_files = 0;
+            let mut old_files = Vec::new();
+            for entry in WalkDir::new(long_path(&path)).into_iter().flatten() {
+                let Ok(metadata) = entry.metadata() else {
+                    continue;
+                };
+                if !metadata.is_file() {
+                    continue;
+                }
+
+                total_files += 1;
+                if days_since(metadata.modified().ok()) > older_than_days {
+                    old_files.push((entry.into_path(), metadata.len()));
+                }
+            }
+
+            // Trimming everything is just a delete, and the next open would have to pull the whole model down again
+            if !old_files.is_empty() && old_files.len() == total_files && !force {
+                return Err(AppError::Invalid(format!(
+                    "Every file in project with ID '{}' is older than {} days, trimming would empty it.", trim_id, older_than_days
+                )));
+            }
+
+            let mut trimmed = TrimResult { bytes_freed: 0, files_removed: 0, failed: Vec::new() };
+
+            // One file failing (say Revit has it open) shouldn't leave the rest behind, so note it and carry on
+            if permanent && !dry_run {
+                for (file, size) in &old_files {
+                    match fs::remove_file(file) {
+                        Ok(()) => {
+                            touched_disk = true;
+                            trimmed.files_removed += 1;
+                            trimmed.bytes_freed += size;
+                        }
+                        Err(e) => {
+                            warn!(path = ?file, "Couldn't trim file: {}", e);
+                            trimmed.failed.push(ScanWarning::new(dunce::simplified(file), e));
+                        }
+                    }
+                }
+                return Ok(trimmed);
+            }
+
+            if dry_run {
+                info!(id = %trim_id, count = old_files.len(), "Dry run, would trim files");
+            } else if !old_files.is_empty() {
+                // The recycle bin doesn't take \\?\ paths, so hand it the normal form the same as a whole-project delete.
+                // It's a single call for every file, so a failure partway through can still have moved some of them.
+                touched_disk = true;
+                trash::delete_all(old_files.iter().map(|(file, _)| dunce::simplified(file)))
+                    .map_err(|e| AppError::Io(format!("Failed to send files in {:?} to the recycle bin: {}", path, e)))?;
+            }
+            trimmed.files_removed = old_files.len() as u64;
+            trimmed.bytes_freed = old_files.iter().map(|(_, size)| size).sum();
+            Ok(trimmed)
+        };
+        let trimmed = trim();
+        (trimmed, touched_disk)
+    }).await.map_err(|e| AppError::Io(format!("Trimming stopped unexpectedly: {}", e)))?;
 
-#[derive(Debug, Serialize, Clone)]
-struct ProjectDef {
-      id: String,
-    name: String,
-    year: u16,
-    days: u64,
-    size: u64,
+    // The cached sizes are wrong as soon as anything has gone, whether or not the whole trim worked
+    if touched_disk {
+        last_scan.mark_stale().await;
+    }
+    let trimmed = trimmed?;
+    info!(
+        id = %id, files_removed = trimmed.files_removed, bytes_freed = trimmed.bytes_freed, failed = trimmed.failed.len(),
+        dry_run, "Trimmed project"
+    );
+    Ok(trimmed)
 }
 
-pub struct ProjectCache(pub Mutex<HashMap<String, PathBuf>>);
+// Finds the newest modified time of any file under a folder
+fn newest_modified(path: &Path) -> Option<std::time::SystemTime> {
+    walk_project(path, &ScanOptions::default(), &ScanStop::never())?.youngest
+}
 
-pub struct AppPaths {
-    revit_cc_base: PathBuf,
+// Sums the size of every file under a folder, skipping anything we can't read
+fn folder_size(path: &Path) -> u64 {
+    folder_totals(path).0
 }
 
-const MINIMUM_VERSION: u16 = 2018;
-const MAXIMUM_VERSION: u16 = 2038;
+// Same as folder_size, but also counts the files, returned as (bytes, files)
+fn folder_totals(path: &Path) -> (u64, u64) {
+    walk_project(path, &ScanOptions::default(), &ScanStop::never())
+        .map(|walk| (walk.total_size, walk.file_count))
+        .unwrap_or_default()
+}
+
+// Adds up a folder after making sure it's one of ours, so the frontend can't use this to walk anywhere on the disk
+fn measure(path: &Path, roots: &ScanRoots) -> Result<PathMeasure, AppError> {
+    if !path.is_dir() {
+        return Err(AppError::PathMissing(format!("Directory {:?} does not exist.", path)));
+    }
+    if !is_within_roots(path, roots.revit_cc_base.as_deref(), &roots.extra_roots) {
+        return Err(AppError::Invalid(format!("{:?} is not inside the Revit cache.", path)));
+    }
+
+    let (total_bytes, file_count) = folder_totals(path);
+    Ok(PathMeasure { total_bytes, file_count })
+}
 
 #[tauri::command]
-async fn get_projects(
-    paths: State<'_, AppPaths>,
+async fn measure_path(
+    path: String,
+    settings: State<'_, SettingsState>
+) -> Result<PathMeasure, AppError> { // Returns the folder's totals on Ok, or an Err if it isn't a folder inside the cache
+    measure(Path::new(&path), &settings.snapshot().await)
+}
+
+// Evens out a path so the one from a log and the one from a scan compare equal. The file might be long gone so it isn't
+// always possible to canonicalize, and Windows paths don't care about case.
+fn comparable_path(path: &Path) -> PathBuf {
+    let path = dunce::canonicalize(path).unwrap_or_else(|_| dunce::simplified(path).to_path_buf());
+    if cfg!(windows) {
+        PathBuf::from(path.to_string_lossy().to_lowercase())
+    } else {
+        path
+    }
+}
+
+#[tauri::command]
+async fn resolve_project_by_path(
+    path: String,
     cache: State<'_, ProjectCache>
-) -> Result<Vec<ProjectDef>, ()> { // Result is eiter a Vector on Ok, or a void error.
+) -> Result<String, AppError> { // Returns the id of the project the path is in on Ok, or an Err if no project contains it
+    let target = comparable_path(Path::new(&path));
 
-    // Container vector
-    let mut all_projects = Vec::new();
+    // Copy the paths out so the cache isn't locked while they get canonicalized
+    let project_paths = cache.0.lock().await.clone();
 
-    // Clear cache on run
-    cache.0.lock().await.clear();
+    // Projects don't sit inside each other, but if one somehow does the deepest one is the owner
+    project_paths.iter()
+        .map(|(id, project_path)| (id, comparable_path(project_path)))
+        .filter(|(_, project_path)| target.starts_with(project_path))
+        .max_by_key(|(_, project_path)| project_path.components().count())
+        .map(|(id, _)| id.clone())
+        .ok_or_else(|| AppError::NotInCache(format!("{} isn't inside any project from the last scan.", path)))
+}
+
+#[tauri::command]
+async fn estimate_delete(
+    ids: Vec<String>,
+    cache: State<'_, ProjectCache>
+) -> Result<DeleteEstimate, ()> { // Always returns an Ok, ids that can't be found just don't add anything
+    let DeletePreview { total_bytes, total_files, .. } = build_delete_preview(&ids, &cache).await;
 
-    // Iterate over each year
-    for vers in MINIMUM_VERSION..=MAXIMUM_VERSION {
+    // Deleting is mostly per-file overhead with a bit on top for the amount of data
+    let estimated_secs = total_files / DELETE_FILES_PER_SEC + total_bytes / DELETE_BYTES_PER_SEC;
 
-        // Create path: %localappdata%/Autodesk/Revit/Autodesk Revit {vers}/CollaborationCache
-        let vers_path = &paths.revit_cc_base
-            .join(format!("Autodesk Revit {}", vers))
-            .join("CollaborationCache");
+    Ok(DeleteEstimate { total_bytes, total_files, estimated_secs })
+}
 
-        // Check that the path exists first
-        if !vers_path.exists() {
-            continue;
+#[tauri::command]
+async fn delete_folders_preview(
+    ids: Vec<String>,
+    cache: State<'_, ProjectCache>
+) -> Result<DeletePreview, ()> { // Always returns an Ok, nothing is touched on disk
+    Ok(build_delete_preview(&ids, &cache).await)
+}
+
+// Measures each id's folder for a preview without touching anything on disk
+async fn build_delete_preview(ids: &[String], cache: &ProjectCache) -> DeletePreview {
+    // Look up the paths without removing them from the cache, since this is only a preview
+    let paths: Vec<(String, Option<PathBuf>)> = {
+        let cache_guard = cache.0.lock().await;
+        ids.iter().map(|id| (id.clone(), cache_guard.get(id).cloned())).collect()
+    };
+
+    // Measuring means walking every folder, so keep it off the async runtime
+    let preview = tauri::async_runtime::spawn_blocking(move || {
+        // Container variables for the summary
+        let mut total_bytes: u64 = 0;
+        let mut total_files: u64 = 0;
+        let mut folders = HashMap::new();
+        let mut missing = Vec::new();
+
+        for (id, path) in paths {
+            match path {
+                // If the folder is still there, measure it
+                Some(path) if path.exists() => {
+                    let (bytes, files) = folder_totals(&path);
+                    total_bytes += bytes;
+                    total_files += files;
+                    folders.insert(id, bytes);
+                }
+                // Either it isn't in the cache or it has disappeared since the last scan
+                _ => missing.push(id),
+            }
         }
 
-        // Get an iterator of user folders in the current version folder
-        let user_folders = match fs::read_dir(vers_path) {
-            Ok(folders) => folders,
-            Err(_) => continue,
-        };
+        DeletePreview { total_bytes, total_files, folders, missing }
+    }).await;
+
+    // Nothing got measured, so none of them can be vouched for
+    preview.unwrap_or_else(|e| {
+        error!("Delete preview stopped unexpectedly: {}", e);
+        DeletePreview { total_bytes: 0, total_files: 0, folders: HashMap::new(), missing: ids.to_vec() }
+    })
+}
 
-        // Loop over the user folders
-        for user_folder in user_folders {
+// Reads the saved project cache, dropping any entries whose folders have been removed since it was written
+fn load_persisted_projects(app: &AppHandle) -> PersistedProjects {
+    let mut persisted: PersistedProjects = app.path()
+        .app_data_dir()
+        .ok()
+        .and_then(|dir| fs::read_to_string(dir.join(PROJECTS_FILE)).ok())
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default();
 
-            // Check that we've gotten a user folder on this iteration
-            let user_folder_entry = match user_folder {
-                Ok(entry) => entry,
-                Err(_) => continue,
-            };
+    persisted.projects.retain(|(_, path)| path.exists());
+    persisted
+}
 
-            // Check that it's a directory
-            let user_folder_path = user_folder_entry.path();
-            if !user_folder_path.is_dir() {
-                continue;
-            }
+// Writes the current project cache and last scan results to the app data directory
+fn save_persisted_projects(app: &AppHandle) -> Result<(), AppError> {
+    // Pair each project from the last scan with its path from the cache
+    let cache = app.state::<ProjectCache>();
+    let cache_guard = cache.0.blocking_lock();
+    let last_scan = app.state::<LastScan>();
+    let projects = last_scan.projects.blocking_lock()
+        .iter()
+        .filter_map(|project| cache_guard.get(&project.id).map(|path| (project.clone(), path.clone())))
+        .collect();
+    let scanned_at = last_scan.stamp.blocking_lock().scanned_at;
 
-            // Get an iterator for the project folders in the current user folder
-            let project_folders = match fs::read_dir(user_folder_path) {
-                Ok(folders) => folders,
-                Err(_) => continue,
-            };
+    let dir = app.path().app_data_dir().map_err(AppError::from)?;
+    fs::create_dir_all(&dir).map_err(AppError::from)?;
 
-            // Loop over the project folders
-            for project_folder in project_folders {
+    let contents = serde_json::to_string(&PersistedProjects { projects, scanned_at }).map_err(AppError::from)?;
+    fs::write(dir.join(PROJECTS_FILE), contents).map_err(AppError::from)
+}
 
-                // Check that we've gotten a project folder this iteration
-                let project_folder_entry = match project_folder {
-                    Ok(entry) => entry,
-                    Err(_) => continue,
-                };
+// Turns a byte count into something readable like "1.4 GB", the same way the frontend does
+fn format_bytes(size: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
 
-                // Get the path to the folder and make an id for it
-                let project_folder_path = project_folder_entry.path();
-                let path_id = project_folder_path.to_string_lossy().into_owned();
-
-                // Get the name of the folder itself to show in the UI
-                let project_name = project_folder_entry.file_name().to_string_lossy().into_owned();
-
-                // Set up container variables for the size and creation date of the newest file
-                let mut total_size: u64 = 0;
-                let mut youngest_file_time: Option<std::time::SystemTime> = None;
-
-                // Walk the directory for each file/folder
-                for entry in WalkDir::new(&project_folder_path) {
-                    // Check that we're able to get the entry from the current step of the walk
-                    if let Ok(entry) = entry {
-                        // Make sure that entry returns metadata
-                        if let Ok(metadata) = entry.metadata() {
-                            // Check the current entry is a file (i.e. not a folder)
-                            if !metadata.is_file() {
-                                continue;
-                            }
-
-                            // Add the size of the current entry to the total size for the project
-                            total_size += metadata.len();
-
-                            // Check that the entry has a modified time
-                            if let Ok(modified_time) = metadata.modified() {
-                                // Check if the new time is younger than the current youngest time
-                                match youngest_file_time {
-                                    Some(current_youngest) if modified_time > current_youngest => {
-                                        youngest_file_time = Some(modified_time);
-                                    }
-                                    None => {
-                                        youngest_file_time = Some(modified_time);
-                                    }
-                                    // Do nothing if its not
-                                    _ => {}
-                                }
-                            }
-                        }    
-                    }
-                }
+    // Step up a unit for every 1024, stopping at the biggest one we have. Anything that would round up to 1024 steps up
+    // too, so 1024² - 1 shows as "1 MB" rather than "1024 KB".
+    let mut value = size as f64;
+    let mut index = 0;
+    while (value * 100.0).round() >= 1024.0 * 100.0 && index < UNITS.len() - 1 {
+        value /= 1024.0;
+        index += 1;
+    }
 
-                // Convert the system time into a number of days
-                let mut days_old: u64 = 0;
-                if let Some(youngest_time) = youngest_file_time {
-                    if let Ok(duration) = std::time::SystemTime::now().duration_since(youngest_time) {
-                        days_old = duration.as_secs() / 86400; // 86400 = 60*60*24
-                    }
-                }
+    // Round to 2 decimals and drop any trailing zeros
+    let rounded = format!("{:.2}", value);
+    let trimmed = rounded.trim_end_matches('0').trim_end_matches('.');
+    format!("{} {}", trimmed, UNITS[index])
+}
 
-                // Set up the definition for the project to pass to the frontend
-                let new_project = ProjectDef {
-                    id: path_id.clone(),
-                    name: project_name,
-                    year: vers,
-                    size: total_size,
-                    days: days_old,
-                };
+// Wraps a value in quotes for a CSV file, doubling up any quotes inside it
+fn csv_quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('"', "\"\""))
+}
 
-                // Push that definition onto the output vector
-                all_projects.push(new_project);
+#[tauri::command]
+fn export_csv(
+    projects: Vec<ProjectDef>,
+    out_path: String
+) -> Result<String, AppError> { // Returns the path that was written on Ok
+    write_csv(&projects, &out_path)?;
+    Ok(out_path)
+}
 
-                // Add the id:path pair to the cach to keep a referenceable pristine PathBuf copy of the path on the backend
-                cache.0.lock().await.insert(path_id, project_folder_path);
+#[tauri::command]
+async fn export_selected_csv(
+    ids: Vec<String>,
+    out_path: String,
+    last_scan: State<'_, LastScan>
+) -> Result<SelectedExport, AppError> { // Returns the path written and any ids that weren't in the last scan on Ok
+    // Taken from the last scan rather than walked again, in the order they were picked
+    let mut projects = Vec::new();
+    let mut warnings = Vec::new();
+    {
+        let last_scan_guard = last_scan.projects.lock().await;
+        for id in &ids {
+            match last_scan_guard.iter().find(|project| &project.id == id) {
+                Some(project) => projects.push(project.clone()),
+                None => warnings.push(format!("Project with ID '{}' isn't in the last scan, it was left out.", id)),
             }
         }
     }
-    // return all_projects on the Ok of the Result<>
-    Ok(all_projects)
+
+    write_csv(&projects, &out_path)?;
+    Ok(SelectedExport { path: out_path, warnings })
+}
+
+// Writes projects out as a CSV file, one row each
+fn write_csv(projects: &[ProjectDef], out_path: &str) -> Result<(), AppError> {
+    // Header row first
+    let mut contents = String::from("id,name,year,days,size,size_display\n");
+
+    // One row per project, the text fields are quoted in case they contain commas
+    for project in projects {
+        contents.push_str(&format!(
+            "{},{},{},{},{},{}\n",
+            csv_quote(&project.id),
+            csv_quote(&project.name),
+            project.year,
+            project.days,
+            project.size,
+            csv_quote(&format_bytes(project.size)),
+        ));
+    }
+
+    fs::write(out_path, contents)
+        .map_err(|e| AppError::io(Path::new(out_path), e))
 }
 
 #[tauri::command]
-async fn open_project(
+async fn export_json(
+    out_path: String,
+    last_scan: State<'_, LastScan>
+) -> Result<String, AppError> { // Returns the path that was written on Ok
+    let projects = last_scan.projects.lock().await.clone();
+
+    let export = JsonExport {
+        schema_version: EXPORT_SCHEMA_VERSION,
+        exported_at: unix_seconds(std::time::SystemTime::now()),
+        totals: CacheTotals {
+            total_bytes: projects.iter().map(|project| project.size).sum(),
+            project_count: projects.len(),
+        },
+        projects,
+    };
+
+    let contents = serde_json::to_string_pretty(&export).map_err(AppError::from)?;
+    fs::write(&out_path, contents)
+        .map_err(|e| AppError::io(Path::new(&out_path), e))?;
+
+    Ok(out_path)
+}
+
+#[tauri::command]
+async fn run_report(
+    options: Option<ScanOptions>, // Same as get_projects
+    settings: State<'_, SettingsState>,
+    app: AppHandle
+) -> Result<CacheReport, AppError> { // Returns the whole report on Ok, or an Err if the scan couldn't run
+    let options = settings.scan_options(options).await;
+    settings.snapshot().await.base()?;
+    let ScanResult { projects, warnings, truncated, .. } = scan_projects(&app, &options, false).await?;
+    Ok(build_report(projects, warnings, truncated))
+}
+
+// Totals up a finished scan into the report, shared by run_report and the headless --report mode
+fn build_report(projects: Vec<ProjectDef>, warnings: Vec<ScanWarning>, truncated: bool) -> CacheReport {
+    // Total up each version as well as the whole lot
+    let mut versions: BTreeMap<u16, CacheTotals> = BTreeMap::new();
+    for project in &projects {
+        let totals = versions.entry(project.year).or_insert(CacheTotals { total_bytes: 0, project_count: 0 });
+        totals.total_bytes += project.size;
+        totals.project_count += 1;
+    }
+    let totals = CacheTotals {
+        total_bytes: projects.iter().map(|project| project.size).sum(),
+        project_count: projects.len(),
+    };
+
+    info!(projects = projects.len(), total_bytes = totals.total_bytes, "Generated report");
+    CacheReport {
+        schema_version: REPORT_SCHEMA_VERSION,
+        generated_at: unix_seconds(std::time::SystemTime::now()),
+        host_name: sysinfo::System::host_name(),
+        totals,
+        versions,
+        projects,
+        warnings,
+        truncated,
+    }
+}
+
+// Runs the same scan and report as run_report without starting the window, for scheduled tasks and scripts. It reads the
+// saved settings from where the app keeps them but leaves the project cache, names and quarantine alone, since the app
+// might be open at the same time. The report goes to out_path if there is one, otherwise to stdout.
+pub fn run_headless_report(out_path: Option<PathBuf>) -> Result<(), String> {
+    // Tauri puts the app data under the bundle identifier, dirs gives the same folders without needing an app handle
+    let config: serde_json::Value = serde_json::from_str(include_str!("../tauri.conf.json")).map_err(|e| e.to_string())?;
+    let identifier = config["identifier"].as_str().ok_or("tauri.conf.json has no identifier")?;
+    let settings = SettingsState::load(
+        dirs::data_dir().map(|dir| dir.join(identifier)),
+        default_cache_base(dirs::data_local_dir()),
+    );
+
+    let (roots, options, pinned) = tauri::async_runtime::block_on(async {
+        (settings.snapshot().await, settings.scan_options(None).await, settings.pinned().await)
+    });
+    roots.base().map_err(|e| e.to_string())?;
+    options.validate().map_err(|e| e.to_string())?;
+
+    // Same walk as scan_projects, minus the events and the cache since nothing is listening
+    let mut warnings = Vec::new();
+    let project_jobs = find_project_folders(&roots, &mut warnings);
+    let total_jobs = project_jobs.len();
+    let cancelled = AtomicBool::new(false);
+    let stop = ScanStop {
+        cancelled: &cancelled,
+        deadline: options.max_duration_secs.map(|secs| Instant::now() + Duration::from_secs(secs)),
+    };
+    let names = NameCache::new(tauri::async_runtime::block_on(settings.resolved_names()));
+    let scanned: Vec<(ProjectDef, WalkStats)> = project_jobs
+        .into_par_iter()
+        .filter_map(|folder| scan_project_folder(&folder, &options, &stop, &names))
+        .collect();
+    let truncated = scanned.len() < total_jobs;
+
+    let mut projects = Vec::new();
+    for (mut project, walk_stats) in scanned {
+        warnings.extend(walk_stats.warnings);
+        if passes_filters(&project, &options) {
+            project.pinned = pinned.contains(&project.id);
+            projects.push(project);
+        }
+    }
+    sort_projects(&mut projects, options.sort);
+
+    let report = build_report(projects, warnings, truncated);
+    let contents = serde_json::to_string_pretty(&report).map_err(|e| e.to_string())?;
+    match out_path {
+        Some(out_path) => fs::write(&out_path, contents).map_err(|e| AppError::io(&out_path, e).to_string()),
+        None => {
+            println!("{}", contents);
+            Ok(())
+        }
+    }
+}
+
+#[tauri::command]
+async fn set_base_path(
+    path: String,
+    settings: State<'_, SettingsState>
+) -> Result<(), AppError> { // Does not return an Ok, only returns an Err if there is an issue
+    // Make sure the new path is actually a folder before we start scanning it
+    let new_base = PathBuf::from(path);
+    settings::check_dir(&new_base)?;
+
+    // Saved before it's swapped in, so we don't end up using a path that won't be there after a restart. get_projects
+    // and open_vers will pick it up from here.
+    settings.update(|settings| {
+        settings.revit_cc_base = Some(new_base);
+        Ok(())
+    }).await?;
+
+    Ok(())
+}
+
+#[tauri::command]
+async fn get_base_path(
+    settings: State<'_, SettingsState>
+) -> Result<ScanLocations, ()> { // Always returns an Ok, base is None if there's nowhere to look
+    let roots = settings.snapshot().await;
+    let to_display = |path: &Path| dunce::simplified(path).to_string_lossy().into_owned();
+
+    Ok(ScanLocations {
+        base: roots.revit_cc_base.as_deref().map(to_display),
+        custom_base: roots.custom_base,
+        extra_roots: roots.extra_roots.iter().map(|root| to_display(root)).collect(),
+    })
+}
+
+#[tauri::command]
+async fn reset_base_path(
+    settings: State<'_, SettingsState>
+) -> Result<BaseReset, AppError> { // Returns the default path on Ok, or an Err if the settings couldn't be saved
+    // Forgetting the chosen path is all it takes, snapshot falls back to the default when there isn't one
+    settings.update(|settings| {
+        settings.revit_cc_base = None;
+        Ok(())
+    }).await?;
+
+    // A machine without Revit won't have the default folder, which is worth saying but isn't a reason to fail
+    let default_base = settings.default_base();
+    let warning = match default_base {
+        None => Some(BASE_PATH_UNAVAILABLE.to_string()),
+        Some(base) if !base.is_dir() => Some(format!("The default Revit cache path {:?} does not exist, is Revit installed?", base)),
+        Some(_) => None,
+    };
+    info!(base = ?default_base, "Reset the base path to the default");
+
+    Ok(BaseReset {
+        path: default_base.map(|base| base.to_string_lossy().into_owned()),
+        warning,
+    })
+}
+
+#[tauri::command]
+async fn add_cache_root(
+    path: String,
+    settings: State<'_, SettingsState>
+) -> Result<(), AppError> { // Does not return an Ok, only returns an Err if there is an issue
+    // Make sure the root is actually a folder before we start scanning it
+    let new_root = PathBuf::from(path);
+    settings::check_dir(&new_root)?;
+
+    settings.update(|settings| {
+        // Adding the same root twice would show every project in it twice
+        if settings.extra_roots.contains(&new_root) {
+            return Err(AppError::Invalid(format!("{:?} is already being scanned.", new_root)));
+        }
+        settings.extra_roots.push(new_root);
+        Ok(())
+    }).await?;
+
+    Ok(())
+}
+
+#[tauri::command]
+async fn pin_project(
     id: String,
-    cache: State<'_, ProjectCache>,
+    settings: State<'_, SettingsState>,
+    last_scan: State<'_, LastScan>
+) -> Result<(), AppError> { // Does not return an Ok, only returns an Err if there is an issue
+    set_pinned(id, true, &settings, &last_scan).await
+}
+
+#[tauri::command]
+async fn unpin_project(
+    id: String,
+    settings: State<'_, SettingsState>,
+    last_scan: State<'_, LastScan>
+) -> Result<(), AppError> { // Does not return an Ok, only returns an Err if there is an issue
+    set_pinned(id, false, &settings, &last_scan).await
+}
+
+// Pins or unpins a project, saving the pins and keeping the last scan's flag in step
+async fn set_pinned(id: String, pin: bool, settings: &SettingsState, last_scan: &LastScan) -> Result<(), AppError> {
+    settings.update(|settings| {
+        if pin {
+            settings.pinned.insert(id.clone());
+        } else {
+            settings.pinned.remove(&id);
+        }
+        Ok(())
+    }).await?;
+
+    if let Some(project) = last_scan.projects.lock().await.iter_mut().find(|project| project.id == id) {
+        project.pinned = pin;
+    }
+
+    Ok(())
+}
+
+#[tauri::command]
+async fn remove_cache_root(
+    path: String,
+    settings: State<'_, SettingsState>
+) -> Result<(), AppError> { // Does not return an Ok, only returns an Err if there is an issue
+    let root = PathBuf::from(path);
+
+    // Take the root out of the list, erroring if it was never in there
+    settings.update(|settings| {
+        let count_before = settings.extra_roots.len();
+        settings.extra_roots.retain(|existing| existing != &root);
+        if settings.extra_roots.len() == count_before {
+            return Err(AppError::Invalid(format!("{:?} is not an extra cache root.", root)));
+        }
+        Ok(())
+    }).await?;
+
+    Ok(())
+}
+
+#[tauri::command]
+fn open_log_file(
     app: AppHandle
-) -> Result<(), String> { // Does not return an Ok, only returns an Err if there is an issue
-    // Try and pull the path from the id on the cache
-    if let Some(path) = cache.0.lock().await.get(&id) {
-        // Start the app.opener
-        app.opener()
-            // Open the path, passing "none" as the handler to use the default explorer window
-            .open_path(path.to_string_lossy(), None::<&str>)
-            // Map the error to e and return it
-            .map_err(|e| e.to_string())
-    } else {
-        // If we didn't get anything looking for the id in the cache, log an error.
-        Err("Project not found in cache".to_string())
+) -> Result<(), AppError> { // Does not return an Ok, only returns an Err if there is an issue
+    let log_dir = app.try_state::<logging::LogDir>()
+        .map(|log_dir| log_dir.0.clone())
+        .ok_or_else(|| AppError::Io("Logging is not available.".to_string()))?;
+
+    // Select the latest log file in Explorer, or just open the folder if nothing has been written yet
+    match logging::latest_log_file(&log_dir) {
+        Some(log_file) => reveal_in_explorer(&app, &log_file),
+        None => open_in_explorer(&app, &log_dir),
     }
 }
 
 #[tauri::command]
-fn open_vers(
-    vers: u16, // We expect a number like "2025", this should be a "year" from ProjectDef
-    paths: State<'_, AppPaths>,
+async fn start_watch(
+    settings: State<'_, SettingsState>,
+    folder_watch: State<'_, FolderWatch>,
     app: AppHandle
-) -> Result<(), String> {
-    // Create path: %localappdata%/Autodesk/Revit/Autodesk Revit {vers}/CollaborationCache
-    let vers_path = &paths.revit_cc_base
-            .join(format!("Autodesk Revit {}", vers))
-            .join("CollaborationCache");
-    // Start the app.opener
-    app.opener()
-        // Open the path, passing "none" as the handler to use the default explorer window
-        .open_path(vers_path.to_string_lossy(), None::<&str>)
-        // Map the error to e and return it
-        .map_err(|e| e.to_string())
+) -> Result<(), AppError> { // Does not return an Ok, only returns an Err if there is an issue
+    let cache_dirs = settings.snapshot().await.cache_dirs();
+    if cache_dirs.is_empty() {
+        return Err(AppError::PathMissing(BASE_PATH_UNAVAILABLE.to_string()));
+    }
+
+    // Stop any watch that's already going first so roots changed since then get picked up
+    let mut watch_guard = folder_watch.0.lock().await;
+    *watch_guard = None;
+
+    info!(roots = cache_dirs.len(), "Starting folder watch");
+    let watcher = watch::start(&cache_dirs, move |changed| report_changed_projects(&app, &changed))
+        .map_err(AppError::Io)?;
+    *watch_guard = Some(watcher);
+
+    Ok(())
 }
 
 #[tauri::command]
-async fn delete_folder(
-    id: String,
-    cache: State<'_, ProjectCache>
-) -> Result<(), String> { // Does not return an Ok, only retuns an Err if there is an issue
-    // Try and pull the path from the id on the cache
-    if let Some(path_to_delete) = cache.0.lock().await.remove(&id) {
-        // If we get a pth, try and remove it
-        match fs::remove_dir_all(&path_to_delete) {
-            Ok(_) => { // if remove_dir_all returns an Ok
-                println!("Successfully Deleted Directory: {:?}", path_to_delete);
-                Ok(())
-            },
-            Err(e) => { // if remove_dir_all returns an error, we should pass it along
-                let error_message = format!("Failed to delete directory {:?}: {}", path_to_delete, e);
-                println!("{}", error_message);
-                Err(error_message)
-            }
+async fn stop_watch(
+    folder_watch: State<'_, FolderWatch>
+) -> Result<(), ()> { // Always returns an Ok, stopping when nothing is being watched does nothing
+    if folder_watch.0.lock().await.take().is_some() {
+        info!("Stopped folder watch");
+    }
+    Ok(())
+}
+
+// Works out which cached projects the changed paths belong to and re-sizes just those. This runs on the watcher's own
+// thread rather than the async runtime, so blocking on the locks is fine.
+fn report_changed_projects(app: &AppHandle, changed: &HashSet<PathBuf>) {
+    let last_scan_state = app.state::<LastScan>();
+    let affected: Vec<(String, PathBuf)> = app.state::<ProjectCache>().0.blocking_lock()
+        .iter()
+        .filter(|(_, project_path)| changed.iter().any(|path| path.starts_with(project_path)))
+        .map(|(id, project_path)| (id.clone(), project_path.clone()))
+        .collect();
+
+    for (id, project_path) in affected {
+        let removed = !project_path.is_dir();
+        let size = if removed { 0 } else { folder_size(&project_path) };
+
+        // Keep the last scan in step so the saved list matches what the frontend is showing
+        let mut last_scan = last_scan_state.projects.blocking_lock();
+        if removed {
+            last_scan.retain(|project| project.id != id);
+        } else if let Some(project) = last_scan.iter_mut().find(|project| project.id == id) {
+            project.size = size;
+            project.size_display = format_bytes(size);
         }
-    } else {
-        // If we didn't get anything looking for the id in the cache, log an error.
-        Err(format!("Project with ID '{}' not found in cache.", id))
+        drop(last_scan);
+
+        debug!(id = %id, size, removed, "Project changed on disk");
+        let _ = app.emit("project-changed", ProjectChanged { id, size, size_display: format_bytes(size), removed });
+    }
+}
+
+#[tauri::command]
+fn open_app_data(
+    app: AppHandle
+) -> Result<(), AppError> { // Does not return an Ok, only returns an Err if there is an issue
+    // This is where the saved paths and project cache live
+    let data_dir = app.path().app_data_dir().map_err(AppError::from)?;
+
+    // Nothing may have been saved yet, so make sure there's a folder to open
+    fs::create_dir_all(&data_dir).map_err(AppError::from)?;
+
+    open_in_explorer(&app, &data_dir)
+}
+
+#[tauri::command]
+async fn set_version_range(
+    min: u16,
+    max: Option<u16>, // Leave out for no upper limit
+    settings: State<'_, SettingsState>
+) -> Result<(), AppError> { // Does not return an Ok, only returns an Err if there is an issue
+    // Save the new range so it survives a restart, an upside down range gets caught by the validation
+    settings.update(|settings| {
+        settings.version_range = VersionRange { min, max };
+        Ok(())
+    }).await?;
+
+    Ok(())
+}
+
+#[tauri::command]
+async fn get_settings(
+    settings: State<'_, SettingsState>
+) -> Result<Settings, ()> { // Always returns an Ok
+    Ok(settings.get().await)
+}
+
+#[tauri::command]
+async fn update_settings(
+    new_settings: Settings,
+    settings: State<'_, SettingsState>,
+    last_scan: State<'_, LastScan>
+) -> Result<Settings, AppError> { // Returns the saved settings on Ok, or an Err if they aren't valid and nothing was saved
+    let current = settings.get().await;
+
+    // Only check the folders that actually changed, a saved one that's on a drive that isn't plugged in right now is fine
+    if let Some(base) = new_settings.revit_cc_base.as_deref().filter(|base| current.revit_cc_base.as_deref() != Some(*base)) {
+        settings::check_dir(base)?;
     }
+    for root in new_settings.extra_roots.iter().filter(|root| !current.extra_roots.contains(root)) {
+        settings::check_dir(root)?;
+    }
+
+    let saved = settings.update(|settings| {
+        // The saved names are ours to look after, keep them whatever the frontend sent back
+        let resolved_names = std::mem::take(&mut settings.resolved_names);
+        *settings = new_settings;
+        settings.resolved_names = resolved_names;
+        Ok(())
+    }).await?;
+
+    // The pins may have changed, keep the last scan's flags in step
+    for project in last_scan.projects.lock().await.iter_mut() {
+        project.pinned = saved.pinned.contains(&project.id);
+    }
+
+    Ok(saved)
 }
 
+// Where the cache lives when the user hasn't picked a base path, the environment variable wins over the usual spot
+fn default_cache_base(local_data: Option<PathBuf>) -> Option<PathBuf> {
+    match std::env::var_os(CACHE_BASE_ENV).filter(|base| !base.is_empty()) {
+        Some(base) => {
+            info!("Using {} for the base path: {:?}", CACHE_BASE_ENV, base);
+            Some(PathBuf::from(base))
+        }
+        None => local_data.map(|local_data| local_data.join("Autodesk").join("Revit")),
+    }
+}
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
+        .plugin(tauri_plugin_clipboard_manager::init())
         .setup(|app| {
             let handle = app.handle();
             let path_resolver = handle.path();
 
-            let local_data = path_resolver
-                .local_data_dir()
-                .expect("Failed to get local data directory.");
+            // Start logging first so anything that goes wrong during setup ends up in the log
+            if let Ok(log_dir) = path_resolver.app_log_dir() {
+                let _ = fs::create_dir_all(&log_dir);
+                app.manage(logging::LogGuard(logging::init(&log_dir)));
+                app.manage(logging::LogDir(log_dir));
+            }
+
+            // Some odd profile setups don't have a local data directory, keep going without a default rather than crashing
+            let local_data = path_resolver.local_data_dir()
+                .inspect_err(|e| error!("Failed to get local data directory: {}", e))
+                .ok();
+            let default_base = default_cache_base(local_data);
 
-            let revit_cc_base = local_data
-                .join("Autodesk")
-                .join("Revit");
+            // The saved settings fall back to the default location if no base path has been chosen
+            app.manage(SettingsState::load(path_resolver.app_data_dir().ok(), default_base));
 
-            app.manage(AppPaths { revit_cc_base });
-            app.manage(ProjectCache(Mutex::new(HashMap::new())));
+            // Seed the cache and last scan with whatever was saved last session
+            let persisted = load_persisted_projects(handle);
+            let scanned_at = persisted.scanned_at;
+            let cached_paths = persisted.projects.iter()
+                .map(|(project, path)| (project.id.clone(), path.clone()))
+                .collect();
+            let cached_projects = persisted.projects.into_iter()
+                .map(|(project, _)| project)
+                .collect();
+
+            app.manage(ProjectCache(Mutex::new(cached_paths)));
+            // Anything could have changed since last session, so the saved list starts out stale
+            app.manage(LastScan {
+                projects: Mutex::new(cached_projects),
+                stamp: Mutex::new(ScanStamp { scanned_at, stale: true }),
+            });
+            app.manage(CancellationToken(Arc::new(AtomicBool::new(false))));
+            app.manage(ScanInProgress(AtomicBool::new(false)));
+            app.manage(PendingDelete(Mutex::new(None)));
+            app.manage(AutoRefresh(Mutex::new(None)));
+            app.manage(FolderWatch(Mutex::new(None)));
+            // Each session quarantines into its own folder, so nothing from a previous run can be restored over
+            app.manage(Quarantine {
+                dir: path_resolver.app_local_data_dir().ok()
+                    .map(|dir| dir.join(QUARANTINE_DIR).join(Uuid::new_v4().to_string())),
+                entries: Mutex::new(Vec::new()),
+            });
 
             Ok(())
         })
-        .invoke_handler(tauri::generate_handler![get_projects, open_project, open_vers, delete_folder])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .invoke_handler(tauri::generate_handler![get_projects, get_projects_grouped, rescan_project, cancel_scan, open_project, open_project_parent, open_vers, prepare_delete, delete_folder, delete_folders, delete_folders_preview, set_base_path, export_csv, get_total_cache_size, get_disk_space, add_cache_root, remove_cache_root, open_log_file, get_installed_versions, set_version_range, delete_orphaned, open_app_data, get_projects_stream, export_json, get_project_breakdown, get_duplicate_projects, pin_project, unpin_project, start_auto_refresh, stop_auto_refresh, start_watch, stop_watch, delete_version, search_projects, purge_old_revisions, estimate_delete, get_last_results, get_projects_by_user, open_largest_file, restore, restore_last, purge_quarantine, open_model_url, set_delete_concurrency, get_settings, update_settings, copy_path, compare_projects, measure_path, get_thumbnail, get_filtered_total, trim_project, run_report, refresh_names, resolve_project_by_path, preview_clean_all, clean_all, get_age_histogram, open_projects, delete_empty, check_permissions, export_selected_csv, reset_base_path, get_base_path, estimate_scan_cost, get_version_savings])
+        .build(tauri::generate_context!())
+        .expect("error while running tauri application")
+        .run(|app, event| {
+            // Save the project cache on the way out so the next launch can show it straight away
+            if let tauri::RunEvent::Exit = event {
+                if let Err(e) = save_persisted_projects(app) {
+                    error!("Failed to save project cache: {}", e);
+                }
+
+                // Anything still in quarantine is deleted for real now. The whole quarantine folder goes, which also
+                // clears out whatever a crashed session left behind. A dry run leaves it for a later session.
+                let dry_run = app.state::<SettingsState>().blocking_dry_run();
+                if dry_run {
+                    info!("Dry run, leaving the quarantine in place");
+                } else if let Ok(local_data_dir) = app.path().app_local_data_dir() {
+                    let quarantine_root = local_data_dir.join(QUARANTINE_DIR);
+                    if quarantine_root.exists() {
+                        if let Err(e) = fs::remove_dir_all(long_path(&quarantine_root)) {
+                            error!("Failed to purge quarantine: {}", e);
+                        }
+                    }
+                }
+            }
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Lays a project folder out the way Revit does: <base>/Autodesk Revit {year}/CollaborationCache/<user>/<project>
+    fn fake_project(base: &Path, year: u16, user: &str, project: &str) -> PathBuf {
+        let project_path = version_cache_path(base, year).join(user).join(project);
+        fs::create_dir_all(&project_path).unwrap();
+        project_path
+    }
+
+    // Writes a file of the given size, making any folders it needs on the way
+    fn fake_file(path: &Path, len: usize) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, vec![0u8; len]).unwrap();
+    }
+
+    fn roots_for(base: &Path) -> ScanRoots {
+        ScanRoots {
+            revit_cc_base: Some(base.to_path_buf()),
+            custom_base: true,
+            extra_roots: Vec::new(),
+            version_range: VersionRange::default(),
+        }
+    }
+
+    fn scan_one(folder: &ProjectFolder) -> ProjectDef {
+        scan_project_folder(folder, &ScanOptions::default(), &ScanStop::never(), &NameCache::new(HashMap::new()))
+            .expect("scan shouldn't be stopped")
+            .0
+    }
+
+    #[test]
+    fn formats_bytes_at_each_unit_boundary() {
+        let cases = [
+            (0, "0 B"),
+            (1, "1 B"),
+            (1023, "1023 B"),
+            (1024, "1 KB"),
+            (1536, "1.5 KB"),
+            (1024 * 1024 - 1, "1 MB"),
+            (1024 * 1024, "1 MB"),
+            (1024 * 1024 * 1024, "1 GB"),
+            (1024u64.pow(4), "1 TB"),
+            (1024u64.pow(5), "1024 TB"), // Nothing past TB, so it just keeps counting
+            (1288490189, "1.2 GB"),
+            (1234567, "1.18 MB"),
+        ];
+        for (size, expected) in cases {
+            assert_eq!(format_bytes(size), expected, "formatting {}", size);
+        }
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn long_path_extends_disk_and_unc_paths() {
+        let cases = [
+            (r"C:\Users\me\AppData", r"\\?\C:\Users\me\AppData"),
+            (r"\\server\share\cache\project", r"\\?\UNC\server\share\cache\project"),
+            (r"\\server\share", r"\\?\UNC\server\share"),
+            // Already extended-length or relative, both get left alone
+            (r"\\?\C:\Users\me", r"\\?\C:\Users\me"),
+            (r"\\?\UNC\server\share\cache", r"\\?\UNC\server\share\cache"),
+            (r"cache\project", r"cache\project"),
+        ];
+        for (path, expected) in cases {
+            assert_eq!(long_path(Path::new(path)), PathBuf::from(expected), "extending {}", path);
+        }
+
+        // Paths handed to the opener get simplified back, which has to give the path we started with
+        let plain = Path::new(r"C:\Users\me\AppData");
+        assert_eq!(dunce::simplified(&long_path(plain)), plain);
+    }
+
+    #[test]
+    fn walks_projects_past_max_path() {
+        let base = tempfile::tempdir().unwrap();
+        let project = fake_project(base.path(), 2024, "user-a", "Tower");
+
+        // Nest folders until the file path is well past the 260 characters Windows stops at without \\?\
+        let mut deep = project.clone();
+        for level in 0..6 {
+            deep.push(format!("{}_{}", "revision_folder_with_a_long_name_to_fill_the_path", level));
+        }
+        let deep_file = deep.join("data.bin");
+        assert!(deep_file.as_os_str().len() > 260);
+        fs::create_dir_all(long_path(&deep)).unwrap();
+        fs::write(long_path(&deep_file), vec![0u8; 700]).unwrap();
+        fake_file(&project.join("Tower.rvt"), 300);
+
+        let walk = walk_project(&project, &ScanOptions::default(), &ScanStop::never()).unwrap();
+        assert_eq!(walk.file_count, 2);
+        assert_eq!(walk.total_size, 1000);
+        assert!(walk.warnings.is_empty());
+    }
+
+    #[test]
+    fn finds_projects_under_every_installed_version() {
+        let base = tempfile::tempdir().unwrap();
+        fake_project(base.path(), 2023, "user-a", "Tower");
+        fake_project(base.path(), 2024, "user-a", "Office");
+        fake_project(base.path(), 2024, "user-b", "Warehouse");
+
+        // A stray file next to the user folders and a version too old to be in range both get left out
+        fake_file(&version_cache_path(base.path(), 2024).join("notes.txt"), 10);
+        fake_project(base.path(), 2010, "user-a", "Ancient");
+
+        let mut warnings = Vec::new();
+        let mut found: Vec<(u16, String)> = find_project_folders(&roots_for(base.path()), &mut warnings)
+            .into_iter()
+            .map(|folder| (folder.year, folder.name.to_string_lossy().into_owned()))
+            .collect();
+        found.sort();
+
+        assert!(warnings.is_empty(), "{:?}", warnings);
+        assert_eq!(found, vec![
+            (2023, "Tower".to_string()),
+            (2024, "Office".to_string()),
+            (2024, "Warehouse".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn picks_up_versions_past_any_fixed_limit() {
+        let base = tempfile::tempdir().unwrap();
+        fake_project(base.path(), 2045, "user-a", "Tower");
+        fake_project(base.path(), 2060, "user-a", "Office");
+
+        let mut roots = roots_for(base.path());
+        assert_eq!(roots.installed_years(), vec![2045, 2060]);
+
+        // An upper limit the user set still leaves out anything newer
+        roots.version_range.max = Some(2050);
+        assert_eq!(roots.installed_years(), vec![2045]);
+    }
+
+    #[test]
+    fn scans_sizes_and_names_from_the_tree() {
+        let base = tempfile::tempdir().unwrap();
+        let project = fake_project(base.path(), 2024, "user-a", "0f3c2a4e-9b1d-4c6e-8a7f-2d5b6c7e8f90");
+        fake_file(&project.join("Tower.rvt"), 1000);
+        fake_file(&project.join("1").join("data.bin"), 200);
+        fake_file(&project.join("2").join("data.bin"), 300);
+
+        let folders = find_project_folders(&roots_for(base.path()), &mut Vec::new());
+        assert_eq!(folders.len(), 1);
+        let scanned = scan_one(&folders[0]);
+
+        assert_eq!(scanned.id, project_id(&project));
+        assert_eq!(scanned.name, "Tower"); // The model file's name beats the GUID folder name
+        assert_eq!(scanned.year, 2024);
+        assert_eq!(scanned.user_folder, "user-a");
+        assert_eq!(scanned.size, 1500);
+        assert_eq!(scanned.file_count, 3);
+        assert_eq!(scanned.revision_count, 2);
+        assert!(!scanned.empty);
+    }
+
+    #[test]
+    fn keeps_non_ascii_project_names_intact() {
+        let base = tempfile::tempdir().unwrap();
+        let project = fake_project(base.path(), 2024, "user-a", "Projekt_Ärzte_東京");
+        fake_file(&project.join("1").join("data.bin"), 100);
+
+        let folders = find_project_folders(&roots_for(base.path()), &mut Vec::new());
+        assert_eq!(folders.len(), 1);
+        assert_eq!(folders[0].name, std::ffi::OsStr::new("Projekt_Ärzte_東京"));
+
+        let scanned = scan_one(&folders[0]);
+        assert_eq!(scanned.name, "Projekt_Ärzte_東京");
+        assert!(scanned.path_display.ends_with("Projekt_Ärzte_東京"));
+
+        // The id has to come out the same every time, and from the folder rather than anything converted for showing
+        assert_eq!(scanned.id, project_id(&project));
+        assert_eq!(scan_one(&folders[0]).id, scanned.id);
+        assert_ne!(scanned.id, project_id(&project.with_file_name("Projekt_Arzte_東京")));
+    }
+
+    #[test]
+    fn only_purges_old_revision_folders() {
+        let base = tempfile::tempdir().unwrap();
+        let project = fake_project(base.path(), 2024, "user-a", "Tower");
+        let hours_ago = |hours: u64| std::time::SystemTime::now() - Duration::from_secs(hours * 3600);
+        let aged_file = |path: &Path, modified: std::time::SystemTime| {
+            fake_file(path, 100);
+            fs::File::options().write(true).open(path).unwrap().set_modified(modified).unwrap();
+        };
+
+        let revision_guid = "6f1c9a2e-3b4d-4e5f-8a9b-0c1d2e3f4a5b";
+        aged_file(&project.join("1").join("data.bin"), hours_ago(48));
+        aged_file(&project.join(revision_guid).join("data.bin"), hours_ago(24));
+        aged_file(&project.join("2").join("data.bin"), hours_ago(1));
+
+        // Newer than any revision, but neither is a revision so they have to be left alone
+        aged_file(&project.join("Tower_backup").join("Tower.0001.rvt"), hours_ago(0));
+        aged_file(&project.join("Links").join("site.rvt"), hours_ago(72));
+
+        let mut old = old_revisions(&project).unwrap();
+        old.sort();
+        assert_eq!(old, vec![project.join("1"), project.join(revision_guid)]);
+
+        let walk = walk_project(&project, &ScanOptions::default(), &ScanStop::never()).unwrap();
+        assert_eq!(walk.revision_count, 3);
+    }
+
+    #[test]
+    fn flags_projects_with_no_data_as_empty() {
+        let base = tempfile::tempdir().unwrap();
+        let project = fake_project(base.path(), 2024, "user-a", "Crashed");
+        fake_file(&project.join("1").join("empty.bin"), 0);
+
+        let folders = find_project_folders(&roots_for(base.path()), &mut Vec::new());
+        let scanned = scan_one(&folders[0]);
+
+        assert_eq!(scanned.size, 0);
+        assert!(scanned.empty);
+    }
+
+    #[test]
+    fn excluded_folders_keep_a_project_from_looking_empty() {
+        let base = tempfile::tempdir().unwrap();
+        let project = fake_project(base.path(), 2024, "user-a", "Tower");
+        fake_file(&project.join("Backup").join("model.bin"), 500);
+
+        let folders = find_project_folders(&roots_for(base.path()), &mut Vec::new());
+        let options = ScanOptions { excluded_folders: vec!["Backup".to_string()], ..ScanOptions::default() };
+        let (scanned, _) = scan_project_folder(&folders[0], &options, &ScanStop::never(), &NameCache::new(HashMap::new())).unwrap();
+
+        // The walk skipped the only file, but there's still data on disk so it mustn't be offered as empty
+        assert_eq!(scanned.size, 0);
+        assert!(!scanned.empty);
+    }
 }
@@ -0,0 +1,39 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_appender::rolling;
+
+// Log files are named like collabcachecleaner.log.2025-06-01, a new one each day
+const LOG_FILE_PREFIX: &str = "collabcachecleaner.log";
+
+// Where the logs were written to, kept in app state so the frontend can ask to see them
+pub struct LogDir(pub PathBuf);
+
+// Keeps the background log writer alive, logs stop being written once this is dropped. It's never read, only held.
+#[allow(dead_code)]
+pub struct LogGuard(pub Option<WorkerGuard>);
+
+// Starts writing logs to a daily rolling file in the log directory, returning the guard that keeps the writer going
+pub fn init(log_dir: &Path) -> Option<WorkerGuard> {
+    let file_appender = rolling::daily(log_dir, LOG_FILE_PREFIX);
+    let (writer, guard) = tracing_appender::non_blocking(file_appender);
+
+    // If a subscriber is somehow already set there's nothing to do, and no point keeping the writer around
+    tracing_subscriber::fmt()
+        .with_writer(writer)
+        .with_ansi(false)
+        .with_max_level(tracing::Level::DEBUG)
+        .try_init()
+        .ok()
+        .map(|_| guard)
+}
+
+// Finds the log file written to most recently, if there is one
+pub fn latest_log_file(log_dir: &Path) -> Option<PathBuf> {
+    fs::read_dir(log_dir)
+        .ok()?
+        .flatten()
+        .filter(|entry| entry.file_name().to_string_lossy().starts_with(LOG_FILE_PREFIX))
+        .max_by_key(|entry| entry.metadata().and_then(|metadata| metadata.modified()).ok())
+        .map(|entry| entry.path())
+}
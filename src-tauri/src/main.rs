@@ -2,5 +2,16 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 fn main() {
+    // `--report [file]` runs a scan and writes the report JSON without opening the window. Release builds on Windows have
+    // no console, so pass a file (or redirect stdout) to get the output there.
+    let mut args = std::env::args_os().skip(1);
+    if args.next().is_some_and(|arg| arg == "--report") {
+        if let Err(e) = collabcachecleaner_lib::run_headless_report(args.next().map(Into::into)) {
+            eprintln!("Couldn't generate the report: {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
     collabcachecleaner_lib::run()
 }
@@ -0,0 +1,233 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use serde::{Deserialize, Serialize};
+use walkdir::WalkDir;
+
+// Metadata files bigger than this are skipped, anything useful in them is tiny
+const MAX_METADATA_FILE_SIZE: u64 = 64 * 1024;
+
+// Revit appends to the worksharing session log (.slog) each time the model is opened or synced, it sits next to the
+// model or in its backup folder
+const SESSION_LOG_EXTENSION: &str = "slog";
+
+// Image files that might be a saved preview, along with the type to hand the frontend for each
+const THUMBNAIL_TYPES: [(&str, &str); 4] = [("png", "image/png"), ("jpg", "image/jpeg"), ("jpeg", "image/jpeg"), ("bmp", "image/bmp")];
+
+// Revit embeds a PNG preview near the start of the model file, this is as far in as we'll look for it
+const EMBEDDED_PREVIEW_SEARCH_BYTES: u64 = 8 * 1024 * 1024;
+
+// Previews bigger than this are something else, and would be a lot to send over to the frontend anyway
+const MAX_THUMBNAIL_SIZE: u64 = 4 * 1024 * 1024;
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+const PNG_END: &[u8] = b"IEND\xae\x42\x60\x82";
+
+// What we could work out about a project folder beyond its raw name
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProjectMetadata {
+    pub central_guid: String,
+    pub display_name: Option<String>,
+    pub model_url: Option<String>,
+    pub server_hint: Option<String>,
+}
+
+// Checks for the 8-4-4-4-12 hex layout of a GUID, with or without braces
+pub fn is_guid(value: &str) -> bool {
+    let trimmed = value.trim_start_matches('{').trim_end_matches('}');
+    let groups: Vec<&str> = trimmed.split('-').collect();
+
+    groups.len() == 5
+        && groups.iter().zip([8, 4, 4, 4, 12]).all(|(group, length)| {
+            group.len() == length && group.chars().all(|c| c.is_ascii_hexdigit())
+        })
+}
+
+// Normalizes a GUID to lowercase without braces so the same model always compares equal
+fn normalize_guid(value: &str) -> String {
+    value.trim_start_matches('{').trim_end_matches('}').to_lowercase()
+}
+
+// Pulls the first BIM360/ACC or Revit Server looking address out of some text
+fn find_model_url(text: &str) -> Option<String> {
+    ["https://", "http://", "rsn://"].iter().find_map(|scheme| {
+        let start = text.find(scheme)?;
+        let url: String = text[start..]
+            .chars()
+            .take_while(|c| !c.is_whitespace() && !matches!(c, '"' | '\'' | '<' | '>'))
+            .collect();
+        Some(url)
+    })
+}
+
+// Takes the host out of an address, which is enough to tell which server a model lives on
+fn host_of(url: &str) -> Option<String> {
+    let without_scheme = url.split_once("://").map(|(_, rest)| rest)?;
+    let host = without_scheme.split(['/', '?', '#']).next()?;
+    (!host.is_empty()).then(|| host.to_string())
+}
+
+// Looks through the top level of a project folder for anything that tells us what the model is
+pub fn resolve(project_path: &Path, folder_name: &str) -> ProjectMetadata {
+    // The folder is named by the central model GUID, fall back to the raw name if it isn't one
+    let mut metadata = ProjectMetadata {
+        central_guid: if is_guid(folder_name) { normalize_guid(folder_name) } else { folder_name.to_string() },
+        ..Default::default()
+    };
+
+    // Get an iterator over the files in the project folder, if we can't read it there's nothing to resolve
+    let entries = match fs::read_dir(project_path) {
+        Ok(entries) => entries,
+        Err(_) => return metadata,
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if !path.is_file() {
+            continue;
+        }
+
+        // A model file with a real name (rather than another GUID) is the friendliest name we can get
+        let is_model = path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("rvt"));
+        if is_model {
+            if let Some(stem) = path.file_stem().map(|stem| stem.to_string_lossy().into_owned()) {
+                if metadata.display_name.is_none() && !is_guid(&stem) {
+                    metadata.display_name = Some(stem);
+                }
+            }
+            continue;
+        }
+
+        // Otherwise check small files for a model address
+        if metadata.model_url.is_none() {
+            let small_enough = entry.metadata().is_ok_and(|m| m.len() <= MAX_METADATA_FILE_SIZE);
+            if small_enough {
+                if let Ok(bytes) = fs::read(&path) {
+                    metadata.model_url = find_model_url(&String::from_utf8_lossy(&bytes));
+                }
+            }
+        }
+    }
+
+    metadata.server_hint = metadata.model_url.as_deref().and_then(host_of);
+    metadata
+}
+
+// A resolved project saved between runs, along with how new its metadata was when it was read
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedName {
+    pub metadata: ProjectMetadata,
+    pub modified_ms: u64,
+}
+
+// The newest change to anything resolve() reads. The folder's own time moves when a model file is added, removed or
+// renamed, and the small files get their own time bumped when they're rewritten. Model files are left out since they
+// change on every sync without their name changing.
+pub fn metadata_modified(project_path: &Path) -> Option<u64> {
+    let folder_time = fs::metadata(project_path).ok()?.modified().ok()?;
+    let newest_file_time = fs::read_dir(project_path).ok()?
+        .flatten()
+        .filter(|entry| !entry.path().extension().is_some_and(|ext| ext.eq_ignore_ascii_case("rvt")))
+        .filter_map(|entry| entry.metadata().ok())
+        .filter(|metadata| metadata.is_file() && metadata.len() <= MAX_METADATA_FILE_SIZE)
+        .filter_map(|metadata| metadata.modified().ok())
+        .max();
+
+    let newest = newest_file_time.map_or(folder_time, |file_time| file_time.max(folder_time));
+    newest.duration_since(UNIX_EPOCH).ok().map(|since_epoch| since_epoch.as_millis() as u64)
+}
+
+// Saved resolutions from earlier scans, keyed by project id since the same GUID shows up once per Revit version.
+// Anything that has to be read fresh gets noted down so the caller can save it once the scan is done. Scans run on the
+// rayon pool, so this uses a plain blocking lock.
+pub struct NameCache {
+    known: HashMap<String, CachedName>,
+    fresh: Mutex<HashMap<String, CachedName>>,
+}
+
+impl NameCache {
+    pub fn new(known: HashMap<String, CachedName>) -> Self {
+        NameCache { known, fresh: Mutex::new(HashMap::new()) }
+    }
+
+    // Uses the saved resolution if the metadata hasn't changed since, otherwise reads it again
+    pub fn resolve(&self, id: &str, project_path: &Path, folder_name: &str) -> ProjectMetadata {
+        let Some(modified_ms) = metadata_modified(project_path) else {
+            return resolve(project_path, folder_name);
+        };
+
+        if let Some(cached) = self.known.get(id).filter(|cached| cached.modified_ms == modified_ms) {
+            return cached.metadata.clone();
+        }
+
+        let metadata = resolve(project_path, folder_name);
+        if let Ok(mut fresh) = self.fresh.lock() {
+            fresh.insert(id.to_string(), CachedName { metadata: metadata.clone(), modified_ms });
+        }
+        metadata
+    }
+
+    // The resolutions that were read fresh and need saving
+    pub fn into_fresh(self) -> HashMap<String, CachedName> {
+        self.fresh.into_inner().unwrap_or_default()
+    }
+}
+
+// Finds when Revit last touched the model, going by the newest session log in or just under the project folder
+pub fn last_opened(project_path: &Path) -> Option<SystemTime> {
+    WalkDir::new(project_path)
+        .max_depth(2)
+        .into_iter()
+        .flatten()
+        .filter(|entry| entry.path().extension().is_some_and(|ext| ext.eq_ignore_ascii_case(SESSION_LOG_EXTENSION)))
+        .filter_map(|entry| entry.metadata().ok()?.modified().ok())
+        .max()
+}
+
+// Finds a preview image for a project, either an image file saved in the folder or the PNG Revit embeds in the model.
+// Returns the image's type and bytes.
+pub fn find_thumbnail(project_path: &Path) -> Option<(&'static str, Vec<u8>)> {
+    let files: Vec<_> = WalkDir::new(project_path)
+        .max_depth(2)
+        .into_iter()
+        .flatten()
+        .filter(|entry| entry.file_type().is_file())
+        .collect();
+
+    // A saved image is the cheapest to get at, so check for one of those first
+    let saved_image = files.iter().find_map(|entry| {
+        let extension = entry.path().extension()?.to_string_lossy().to_lowercase();
+        let (_, mime) = THUMBNAIL_TYPES.iter().find(|(image_extension, _)| *image_extension == extension)?;
+        let small_enough = entry.metadata().is_ok_and(|metadata| metadata.len() <= MAX_THUMBNAIL_SIZE);
+        small_enough.then(|| fs::read(entry.path()).ok()).flatten().map(|bytes| (*mime, bytes))
+    });
+    if saved_image.is_some() {
+        return saved_image;
+    }
+
+    files.iter()
+        .filter(|entry| entry.path().extension().is_some_and(|ext| ext.eq_ignore_ascii_case("rvt")))
+        .find_map(|entry| embedded_preview(entry.path()))
+        .map(|bytes| ("image/png", bytes))
+}
+
+// Pulls the embedded PNG out of the start of a model file, if there's a whole one in there
+fn embedded_preview(model_path: &Path) -> Option<Vec<u8>> {
+    let mut head = Vec::new();
+    fs::File::open(model_path).ok()?
+        .take(EMBEDDED_PREVIEW_SEARCH_BYTES)
+        .read_to_end(&mut head)
+        .ok()?;
+
+    let start = find_bytes(&head, PNG_SIGNATURE)?;
+    let end = start + find_bytes(&head[start..], PNG_END)? + PNG_END.len();
+    let preview = &head[start..end];
+    (preview.len() as u64 <= MAX_THUMBNAIL_SIZE).then(|| preview.to_vec())
+}
+
+fn find_bytes(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|window| window == needle)
+}
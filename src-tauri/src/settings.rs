@@ -0,0 +1,230 @@
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+use tauri::async_runtime::Mutex;
+use tracing::{info, warn};
+use crate::error::AppError;
+use crate::metadata::CachedName;
+use crate::{ScanOptions, ScanRoots, VersionRange, MAX_DELETE_CONCURRENCY, MINIMUM_VERSION};
+
+// File in the app data directory all the settings are saved to
+const SETTINGS_FILE: &str = "settings.json";
+
+// Where the paths and pins used to be saved before there was a settings file, read once so nobody loses their setup
+const LEGACY_PATHS_FILE: &str = "paths.json";
+
+// The upper limit there used to be on versions. A saved range ending there was almost always the default, so it's read
+// as no limit at all.
+const LEGACY_MAXIMUM_VERSION: u16 = 2038;
+
+// Everything the user can change that needs to survive a restart, saved as one file. Anything missing from the file
+// (like a setting added since it was written) falls back to its default.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct Settings {
+    pub revit_cc_base: Option<PathBuf>, // Only set if the user picked one, otherwise the default location under local app data is used
+    pub extra_roots: Vec<PathBuf>, // Relocated CollaborationCache folders to scan as well, laid out as user folders -> project folders
+    pub version_range: VersionRange,
+    pub pinned: BTreeSet<String>, // Ids of the projects that must never be deleted, sorted so the file doesn't shuffle between saves
+    pub delete_concurrency: Option<usize>, // None to pick based on the drive the cache is on
+    pub scan: ScanOptions, // The thresholds and exclusions a scan uses when the frontend doesn't send its own
+    pub resolved_names: HashMap<String, CachedName>, // Names and addresses read out of each project's metadata, by id
+    pub dry_run: bool, // Deletes only log what they would do and report the savings, nothing on disk gets touched
+}
+
+impl Settings {
+    // Catches anything that would break a scan or a delete before it gets saved
+    pub fn validate(&self) -> Result<(), AppError> {
+        let VersionRange { min, max } = self.version_range;
+        if let Some(max) = max.filter(|max| min > *max) {
+            return Err(AppError::Invalid(format!("The minimum version ({}) can't be after the maximum version ({}).", min, max)));
+        }
+        if min < MINIMUM_VERSION {
+            return Err(AppError::Invalid(format!("Versions have to be {} or later.", MINIMUM_VERSION)));
+        }
+
+        if let Some(concurrency) = self.delete_concurrency {
+            if !(1..=MAX_DELETE_CONCURRENCY).contains(&concurrency) {
+                return Err(AppError::Invalid(format!("Delete concurrency has to be between 1 and {}.", MAX_DELETE_CONCURRENCY)));
+            }
+        }
+
+        self.scan.validate()?;
+
+        // The same root twice would show every project in it twice
+        let unique_roots: BTreeSet<&PathBuf> = self.extra_roots.iter().collect();
+        if unique_roots.len() != self.extra_roots.len() {
+            return Err(AppError::Invalid("The same cache root can't be added twice.".to_string()));
+        }
+
+        Ok(())
+    }
+}
+
+// The paths file from before the settings file, only ever read
+#[derive(Debug, Deserialize, Default)]
+#[serde(default)]
+struct LegacyPaths {
+    revit_cc_base: Option<PathBuf>,
+    extra_roots: Vec<PathBuf>,
+    version_range: Option<VersionRange>,
+    pinned: Vec<String>,
+    delete_concurrency: Option<usize>,
+}
+
+impl From<LegacyPaths> for Settings {
+    fn from(legacy: LegacyPaths) -> Self {
+        Settings {
+            revit_cc_base: legacy.revit_cc_base,
+            extra_roots: legacy.extra_roots,
+            version_range: legacy.version_range.unwrap_or_default(),
+            pinned: legacy.pinned.into_iter().collect(),
+            delete_concurrency: legacy.delete_concurrency,
+            ..Default::default()
+        }
+    }
+}
+
+// The settings in app state, every command reads them from here rather than keeping its own copy
+pub struct SettingsState {
+    settings: Mutex<Settings>,
+    default_base: Option<PathBuf>, // Where Revit keeps its cache when nobody has moved it, None if local app data couldn't be found
+    dir: Option<PathBuf>, // The app data directory the settings are saved in
+}
+
+impl SettingsState {
+    // Reads the saved settings, falling back to the old paths file and then to the defaults if there's nothing usable
+    pub fn load(dir: Option<PathBuf>, default_base: Option<PathBuf>) -> Self {
+        let read = |file: &str| dir.as_ref().and_then(|dir| fs::read_to_string(dir.join(file)).ok());
+
+        let mut settings: Settings = match read(SETTINGS_FILE) {
+            Some(contents) => serde_json::from_str(&contents).unwrap_or_else(|e| {
+                warn!("Settings file couldn't be read, using the defaults: {}", e);
+                Settings::default()
+            }),
+            None => read(LEGACY_PATHS_FILE)
+                .and_then(|contents| serde_json::from_str::<LegacyPaths>(&contents).ok())
+                .map(|legacy| {
+                    info!("Moving the saved paths over to the settings file");
+                    Settings::from(legacy)
+                })
+                .unwrap_or_default(),
+        };
+        if settings.version_range.max == Some(LEGACY_MAXIMUM_VERSION) {
+            settings.version_range.max = None;
+        }
+
+        SettingsState { settings: Mutex::new(settings), default_base, dir }
+    }
+
+    // Where the base path goes back to when the user hasn't picked one
+    pub fn default_base(&self) -> Option<&Path> {
+        self.default_base.as_deref()
+    }
+
+    pub async fn get(&self) -> Settings {
+        self.settings.lock().await.clone()
+    }
+
+    // Makes a change to the settings, only keeping it if the result is valid and has been saved. The lock is held the
+    // whole time so two changes can't overwrite each other.
+    pub async fn update<F>(&self, change: F) -> Result<Settings, AppError>
+    where
+        F: FnOnce(&mut Settings) -> Result<(), AppError>,
+    {
+        let mut settings_guard = self.settings.lock().await;
+        let mut updated = settings_guard.clone();
+        change(&mut updated)?;
+        updated.validate()?;
+
+        self.save(&updated)?;
+        *settings_guard = updated.clone();
+        Ok(updated)
+    }
+
+    // Writes to a temporary file first and then swaps it in, so a crash partway through can't leave half a file behind
+    fn save(&self, settings: &Settings) -> Result<(), AppError> {
+        let dir = self.dir.as_deref()
+            .ok_or_else(|| AppError::PathMissing("Couldn't find the app data directory to save settings to.".to_string()))?;
+        fs::create_dir_all(dir).map_err(|e| AppError::io(dir, e))?;
+
+        let contents = serde_json::to_string_pretty(settings).map_err(AppError::from)?;
+        let path = dir.join(SETTINGS_FILE);
+        let temp_path = path.with_extension("json.tmp");
+        fs::write(&temp_path, contents).map_err(|e| AppError::io(&temp_path, e))?;
+        fs::rename(&temp_path, &path).map_err(|e| AppError::io(&path, e))
+    }
+
+    // The scan options from the frontend if it sent any, otherwise the saved ones
+    pub async fn scan_options(&self, options: Option<ScanOptions>) -> ScanOptions {
+        match options {
+            Some(options) => options,
+            None => self.settings.lock().await.scan.clone(),
+        }
+    }
+
+    // A copy of the current paths taken at the start of a command, so nothing stays locked during a long walk. A saved
+    // base path is used even if it's gone missing, falling back to the default would quietly scan somewhere else.
+    pub async fn snapshot(&self) -> ScanRoots {
+        let settings = self.settings.lock().await;
+        let custom_base = settings.revit_cc_base.is_some();
+        let revit_cc_base = settings.revit_cc_base.clone().or_else(|| self.default_base.clone());
+
+        ScanRoots {
+            revit_cc_base,
+            custom_base,
+            extra_roots: settings.extra_roots.clone(),
+            version_range: settings.version_range,
+        }
+    }
+
+    pub async fn is_pinned(&self, id: &str) -> bool {
+        self.settings.lock().await.pinned.contains(id)
+    }
+
+    pub async fn pinned(&self) -> BTreeSet<String> {
+        self.settings.lock().await.pinned.clone()
+    }
+
+    pub async fn dry_run(&self) -> bool {
+        self.settings.lock().await.dry_run
+    }
+
+    // Same as dry_run, for the exit handler which isn't async
+    pub fn blocking_dry_run(&self) -> bool {
+        self.settings.blocking_lock().dry_run
+    }
+
+    pub async fn resolved_names(&self) -> HashMap<String, CachedName> {
+        self.settings.lock().await.resolved_names.clone()
+    }
+
+    // Saves the names a scan had to read fresh. If the scan covered every project, names for projects that are gone get
+    // dropped too so the file doesn't keep growing. Nothing gets written if nothing changed.
+    pub async fn save_resolved_names(&self, fresh: HashMap<String, CachedName>, seen: Option<&BTreeSet<String>>) -> Result<(), AppError> {
+        let stale = match seen {
+            Some(seen) => self.settings.lock().await.resolved_names.keys().any(|id| !seen.contains(id)),
+            None => false,
+        };
+        if fresh.is_empty() && !stale {
+            return Ok(());
+        }
+
+        self.update(|settings| {
+            if let Some(seen) = seen {
+                settings.resolved_names.retain(|id, _| seen.contains(id));
+            }
+            settings.resolved_names.extend(fresh);
+            Ok(())
+        }).await.map(|_| ())
+    }
+}
+
+// Checks a folder picked by the user is actually there before it gets saved
+pub fn check_dir(path: &Path) -> Result<(), AppError> {
+    if !path.is_dir() {
+        return Err(AppError::PathMissing(format!("Directory {:?} does not exist.", path)));
+    }
+    Ok(())
+}
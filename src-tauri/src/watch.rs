@@ -0,0 +1,56 @@
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+// Revit writes in bursts, so wait for things to go quiet for this long before reporting what changed
+const DEBOUNCE: Duration = Duration::from_secs(2);
+
+// Something that never stops writing would hold the update back forever, so report anyway after this long
+const MAX_DEBOUNCE: Duration = Duration::from_secs(10);
+
+// Starts watching the roots, calling on_changed with every path touched during each burst of changes. Everything
+// stops once the returned watcher is dropped.
+pub fn start<F>(roots: &[PathBuf], on_changed: F) -> Result<RecommendedWatcher, String>
+where
+    F: Fn(HashSet<PathBuf>) + Send + 'static,
+{
+    let (sender, receiver) = mpsc::channel::<PathBuf>();
+
+    // The watcher calls back on its own thread, just pass the paths over to be debounced
+    let mut watcher = notify::recommended_watcher(move |result: notify::Result<notify::Event>| {
+        if let Ok(event) = result {
+            for path in event.paths {
+                let _ = sender.send(path);
+            }
+        }
+    }).map_err(|e| e.to_string())?;
+
+    for root in roots {
+        watcher.watch(root, RecursiveMode::Recursive)
+            .map_err(|e| format!("Failed to watch {:?}: {}", root, e))?;
+    }
+
+    // Collect paths until nothing new has come in for the debounce period, then hand them over in one go. The
+    // channel closes when the watcher is dropped, which ends the thread.
+    std::thread::spawn(move || {
+        while let Ok(first) = receiver.recv() {
+            let mut changed = HashSet::from([first]);
+            let started = Instant::now();
+            let mut quiet_from = started;
+            while started.elapsed() < MAX_DEBOUNCE {
+                match receiver.recv_timeout(DEBOUNCE.saturating_sub(quiet_from.elapsed())) {
+                    Ok(path) => {
+                        changed.insert(path);
+                        quiet_from = Instant::now();
+                    }
+                    Err(_) => break,
+                }
+            }
+            on_changed(changed);
+        }
+    });
+
+    Ok(watcher)
+}